@@ -0,0 +1,39 @@
+//! Proof generation with and without `with_proof_index`.
+//!
+//! Run with `cargo bench --bench prove`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use static_merkle_array::sha_hasher::Sha512_256Hasher;
+use static_merkle_array::StaticMerkleArray;
+use std::hint::black_box;
+
+type Sma = StaticMerkleArray<u64, Sha512_256Hasher>;
+
+/// `prove_index` for every leaf, one call at a time.
+fn prove_each(tree: &Sma) -> usize {
+    (0..tree.len())
+        .map(|i| black_box(tree.prove_index(i).unwrap()).siblings.len())
+        .sum()
+}
+
+fn prove_every_index(c: &mut Criterion) {
+    let plain = Sma::new((0..1 << 14).collect());
+    let indexed = plain.clone().with_proof_index();
+    // Build the table outside the measurement.
+    indexed.prove_index(0).unwrap();
+
+    let mut group = c.benchmark_group("prove_every_index_16k");
+    group.bench_function("walk", |b| b.iter(|| prove_each(&plain)));
+    group.bench_function("proof_index", |b| b.iter(|| prove_each(&indexed)));
+    group.bench_function("proof_index_with_build", |b| {
+        b.iter_batched(
+            || plain.clone().with_proof_index(),
+            |tree| prove_each(&tree),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, prove_every_index);
+criterion_main!(benches);
@@ -0,0 +1,172 @@
+//! Derive macros for `static_merkle_array`.
+//!
+//! - `#[derive(MerkleLeaf)]`: canonical field-by-field byte encoding
+//!   (`static_merkle_array::leaf::MerkleLeaf`).
+//! - `#[derive(FieldLeaf)]`: field-element encoding for the MiMC/BN254 hasher
+//!   (`static_merkle_array::mimc_bn254_hasher::FieldLeaf`).
+//!
+//! Fields are always taken in declaration order.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, Path};
+
+/* ------------------------------- MerkleLeaf -------------------------------- */
+
+#[proc_macro_derive(MerkleLeaf)]
+pub fn derive_merkle_leaf(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let krate: Path = parse_quote!(::static_merkle_array::leaf);
+    let trait_path: Path = parse_quote!(#krate::MerkleLeaf);
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone(), &trait_path);
+    let (impl_g, ty_g, where_g) = generics.split_for_impl();
+
+    let (encode, decode) = match &input.data {
+        Data::Struct(s) => {
+            let (pattern, binds) = destructure(&s.fields);
+            let decode = construct(quote!(#name), &s.fields, &trait_path);
+            (
+                quote! {
+                    let #name #pattern = self;
+                    #( #trait_path::encode_leaf(#binds, out); )*
+                },
+                quote! { ::core::option::Option::Some(#decode) },
+            )
+        }
+        Data::Enum(e) => {
+            let mut enc_arms = Vec::new();
+            let mut dec_arms = Vec::new();
+            for (idx, v) in e.variants.iter().enumerate() {
+                let tag = idx as u32;
+                let vname = &v.ident;
+                let (pattern, binds) = destructure(&v.fields);
+                enc_arms.push(quote! {
+                    #name::#vname #pattern => {
+                        #trait_path::encode_leaf(&#tag, out);
+                        #( #trait_path::encode_leaf(#binds, out); )*
+                    }
+                });
+                let ctor = construct(quote!(#name::#vname), &v.fields, &trait_path);
+                dec_arms.push(quote! { #tag => ::core::option::Option::Some(#ctor), });
+            }
+            (
+                quote! {
+                    match self { #( #enc_arms )* }
+                },
+                quote! {
+                    match <u32 as #trait_path>::decode_leaf(input)? {
+                        #( #dec_arms )*
+                        _ => ::core::option::Option::None,
+                    }
+                },
+            )
+        }
+        Data::Union(_) => {
+            return syn::Error::new_spanned(name, "MerkleLeaf cannot be derived for unions")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    quote! {
+        impl #impl_g #trait_path for #name #ty_g #where_g {
+            fn encode_leaf(&self, out: &mut ::std::vec::Vec<u8>) {
+                #encode
+            }
+
+            fn decode_leaf(input: &mut &[u8]) -> ::core::option::Option<Self> {
+                #decode
+            }
+        }
+    }
+    .into()
+}
+
+/* -------------------------------- FieldLeaf -------------------------------- */
+
+#[proc_macro_derive(FieldLeaf)]
+pub fn derive_field_leaf(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let krate: Path = parse_quote!(::static_merkle_array::mimc_bn254_hasher);
+    let trait_path: Path = parse_quote!(#krate::FieldLeaf);
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone(), &trait_path);
+    let (impl_g, ty_g, where_g) = generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(s) => &s.fields,
+        _ => {
+            return syn::Error::new_spanned(name, "FieldLeaf can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+    let (pattern, binds) = destructure(fields);
+
+    quote! {
+        impl #impl_g #trait_path for #name #ty_g #where_g {
+            fn to_frs(&self, out: &mut ::std::vec::Vec<#krate::Fr>) {
+                let #name #pattern = self;
+                #( #trait_path::to_frs(#binds, out); )*
+            }
+        }
+    }
+    .into()
+}
+
+/* --------------------------------- Helpers --------------------------------- */
+
+/// Require `bound` on every type parameter.
+fn add_bounds(mut generics: Generics, bound: &Path) -> Generics {
+    for p in generics.type_params_mut() {
+        p.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+/// A pattern binding every field (`{ a: __field_a, .. }`, `(f0, f1)` or
+/// nothing) and the bound names. Named fields get generated names so a field
+/// called `out` or `input` can't shadow the generated method's parameters.
+fn destructure(fields: &Fields) -> (TokenStream2, Vec<syn::Ident>) {
+    match fields {
+        Fields::Named(f) => {
+            let fields: Vec<_> = f.named.iter().map(|f| f.ident.clone().unwrap()).collect();
+            let names: Vec<_> = fields
+                .iter()
+                .map(|n| format_ident!("__field_{}", n, span = Span::call_site()))
+                .collect();
+            (quote!({ #( #fields: #names ),* }), names)
+        }
+        Fields::Unnamed(f) => {
+            let names: Vec<_> = (0..f.unnamed.len())
+                .map(|i| format_ident!("f{}", i, span = Span::call_site()))
+                .collect();
+            (quote!(( #( #names ),* )), names)
+        }
+        Fields::Unit => (quote!(), Vec::new()),
+    }
+}
+
+/// An expression building `ctor` by decoding each field in order.
+fn construct(ctor: TokenStream2, fields: &Fields, trait_path: &Path) -> TokenStream2 {
+    match fields {
+        Fields::Named(f) => {
+            let inits = f.named.iter().map(|f| {
+                let n = f.ident.as_ref().unwrap();
+                let ty = &f.ty;
+                quote!(#n: <#ty as #trait_path>::decode_leaf(input)?)
+            });
+            quote!(#ctor { #( #inits ),* })
+        }
+        Fields::Unnamed(f) => {
+            let inits = f.unnamed.iter().map(|f| {
+                let ty = &f.ty;
+                quote!(<#ty as #trait_path>::decode_leaf(input)?)
+            });
+            quote!(#ctor( #( #inits ),* ))
+        }
+        Fields::Unit => ctor,
+    }
+}
@@ -0,0 +1,134 @@
+//! Async counterparts of `save_to_file` / `load_from_file` (the `tokio`
+//! feature).
+//!
+//! Files go through `tokio::fs`, and the `bincode` encoding and decoding,
+//! which are synchronous and proportional to the tree size, run on tokio's
+//! blocking pool via `spawn_blocking`, so large trees don't stall the
+//! executor. The file format is the same as the sync methods'.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+
+use crate::{
+    decode_versioned, encode_versioned, io_err, MerkleError, MerkleHasher, MerkleProof,
+    StaticMerkleArray,
+};
+
+/// Run `f` on the blocking pool, re-raising its panic if it had one.
+async fn blocking<R, F>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+async fn write_versioned_async<P, S>(path: P, value: S) -> Result<(), MerkleError>
+where
+    P: AsRef<Path>,
+    S: Serialize + Send + 'static,
+{
+    let bytes = blocking(move || encode_versioned(&value)).await?;
+    tokio::fs::write(&path, bytes).await.map_err(io_err(&path))
+}
+
+async fn read_versioned_async<P, D>(path: P) -> Result<D, MerkleError>
+where
+    P: AsRef<Path>,
+    D: DeserializeOwned + Send + 'static,
+{
+    let bytes = tokio::fs::read(&path).await.map_err(io_err(&path))?;
+    blocking(move || decode_versioned(&bytes)).await
+}
+
+impl<T, H> StaticMerkleArray<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone + Send + 'static,
+    H: MerkleHasher + 'static,
+    H::Digest: Send,
+{
+    /// Async `save_to_file`. The tree is cloned so the encoding can run on
+    /// the blocking pool; use `into_file_async` to hand it over instead.
+    pub async fn save_to_file_async<P: AsRef<Path>>(&self, path: P) -> Result<(), MerkleError>
+    where
+        Self: Clone,
+    {
+        write_versioned_async(path, self.clone()).await
+    }
+
+    /// Like `save_to_file_async`, but consumes the tree instead of cloning it.
+    pub async fn into_file_async<P: AsRef<Path>>(self, path: P) -> Result<(), MerkleError> {
+        write_versioned_async(path, self).await
+    }
+
+    /// Async `load_from_file`.
+    pub async fn load_from_file_async<P: AsRef<Path>>(path: P) -> Result<Self, MerkleError> {
+        read_versioned_async(path).await
+    }
+}
+
+impl<H> MerkleProof<H>
+where
+    H: MerkleHasher + 'static,
+    H::Digest: Send,
+{
+    /// Async `save_to_file`.
+    pub async fn save_to_file_async<P: AsRef<Path>>(&self, path: P) -> Result<(), MerkleError>
+    where
+        Self: Clone,
+    {
+        write_versioned_async(path, self.clone()).await
+    }
+
+    /// Async `load_from_file`.
+    pub async fn load_from_file_async<P: AsRef<Path>>(path: P) -> Result<Self, MerkleError> {
+        read_versioned_async(path).await
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+
+    type Sma = StaticMerkleArray<u64, Sha512_256Hasher>;
+
+    #[test]
+    fn async_roundtrip_matches_sync_format() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let dir = std::env::temp_dir();
+        let tree_path = dir.join(format!("sma_async_tree_{}.bin", std::process::id()));
+        let proof_path = dir.join(format!("sma_async_proof_{}.bin", std::process::id()));
+
+        let sm = Sma::new((0..9).collect());
+        let proof = sm.prove_index(4).unwrap();
+        rt.block_on(async {
+            sm.save_to_file_async(&tree_path).await.unwrap();
+            let loaded = Sma::load_from_file_async(&tree_path).await.unwrap();
+            assert_eq!(loaded.root(), sm.root());
+
+            proof.save_to_file_async(&proof_path).await.unwrap();
+            let loaded = MerkleProof::<Sha512_256Hasher>::load_from_file_async(&proof_path)
+                .await
+                .unwrap();
+            assert_eq!(loaded, proof);
+
+            let missing = dir.join("sma_async_missing.bin");
+            assert!(matches!(
+                Sma::load_from_file_async(&missing).await,
+                Err(MerkleError::Io { path, .. }) if path == missing
+            ));
+        });
+        // Sync and async files are interchangeable.
+        assert_eq!(Sma::load_from_file(&tree_path).unwrap().root(), sm.root());
+
+        let _ = std::fs::remove_file(&tree_path);
+        let _ = std::fs::remove_file(&proof_path);
+    }
+}
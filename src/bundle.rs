@@ -0,0 +1,184 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+
+use crate::{
+    verify_value_with_proof, MerkleError, MerkleHasher, MerkleProof, StaticMerkleArray, FILE_MAGIC,
+    FORMAT_VERSION,
+};
+
+/* -------------------------------------------------------------------------
+Proof Bundle
+------------------------------------------------------------------------- */
+
+/// A root plus selected items and their proofs: what a light client needs to
+/// check those items without the full tree.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize, T: Serialize",
+    deserialize = "H::Digest: DeserializeOwned, T: DeserializeOwned"
+))]
+pub struct ProofBundle<T, H: MerkleHasher> {
+    /// The commitment root every entry is checked against.
+    pub root: H::Digest,
+    /// Items with their membership proofs, in the requested order.
+    pub entries: Vec<(T, MerkleProof<H>)>,
+}
+
+impl<T, H> ProofBundle<T, H>
+where
+    T: Serialize + DeserializeOwned,
+    H: MerkleHasher,
+{
+    /// Whether every entry's proof is for `root` and verifies its item.
+    pub fn verify_all(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|(item, proof)| proof.root == self.root && verify_value_with_proof(item, proof))
+    }
+
+    /// Save the bundle to a file (versioned binary encoding, like
+    /// `StaticMerkleArray::save_to_file`).
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), MerkleError> {
+        crate::write_versioned(path, self)
+    }
+
+    /// Load a bundle previously saved with `save_to_file`.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, MerkleError> {
+        crate::read_versioned(path)
+    }
+
+    /// Verify a bundle as it is read, one entry at a time, without holding
+    /// the whole bundle in memory.
+    ///
+    /// `reader` must yield the bytes `save_to_file` writes (header, then the
+    /// `bincode` encoding). Returns `Ok(false)` as soon as the bundle's root
+    /// isn't `expected_root` or an entry fails `verify_all`'s check, without
+    /// reading further; `Ok(true)` once every entry has verified. Read and
+    /// decoding failures are `MerkleError::Codec`, and a bad header is
+    /// `MerkleError::VersionMismatch`.
+    pub fn verify_stream<R: Read>(
+        mut reader: R,
+        expected_root: &H::Digest,
+    ) -> Result<bool, MerkleError> {
+        let (magic, version): ([u8; 4], u8) = bincode::deserialize_from(&mut reader)?;
+        let found = if magic == FILE_MAGIC { version } else { 0 };
+        if found != FORMAT_VERSION {
+            return Err(MerkleError::VersionMismatch {
+                found,
+                expected: FORMAT_VERSION,
+            });
+        }
+
+        // Same layout as the derived encoding: the root, the entry count,
+        // then each `(item, proof)` pair.
+        let root: H::Digest = bincode::deserialize_from(&mut reader)?;
+        if root != *expected_root {
+            return Ok(false);
+        }
+        let count: u64 = bincode::deserialize_from(&mut reader)?;
+        for _ in 0..count {
+            let (item, proof): (T, MerkleProof<H>) = bincode::deserialize_from(&mut reader)?;
+            if proof.root != root || !verify_value_with_proof(&item, &proof) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<T, H> StaticMerkleArray<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    /// Package the root and the items at `indices` with their proofs.
+    ///
+    /// Returns `MerkleError::IndexOob` if any index is out of range.
+    pub fn proof_bundle(&self, indices: &[usize]) -> Result<ProofBundle<T, H>, MerkleError> {
+        let ctx = self.proof_context();
+        let entries = indices
+            .iter()
+            .map(|&i| {
+                Ok((
+                    self.items.get(i).ok_or(MerkleError::IndexOob)?.clone(),
+                    ctx.prove(i)?,
+                ))
+            })
+            .collect::<Result<_, MerkleError>>()?;
+        Ok(ProofBundle {
+            root: self.root(),
+            entries,
+        })
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+
+    type Sma = StaticMerkleArray<String, Sha512_256Hasher>;
+
+    #[test]
+    fn bundle_roundtrip_and_verify() {
+        let items: Vec<String> = (0..7).map(|i| format!("item-{i}")).collect();
+        let sm = Sma::new(items.clone());
+        let bundle = sm.proof_bundle(&[5, 1]).unwrap();
+        assert_eq!(bundle.entries[0].0, items[5]);
+        assert!(bundle.verify_all());
+
+        let path = std::env::temp_dir().join(format!("sma_bundle_{}.bin", std::process::id()));
+        bundle.save_to_file(&path).unwrap();
+        let loaded = ProofBundle::<String, Sha512_256Hasher>::load_from_file(&path).unwrap();
+        assert_eq!(loaded.root, bundle.root);
+        assert_eq!(loaded.entries, bundle.entries);
+        let _ = std::fs::remove_file(&path);
+
+        let mut swapped = bundle.clone();
+        swapped.entries[0].0 = items[4].clone();
+        assert!(!swapped.verify_all());
+        let mut rerooted = bundle;
+        rerooted.root = Sha512_256Hasher::leaf(&0u8);
+        assert!(!rerooted.verify_all());
+
+        assert!(matches!(sm.proof_bundle(&[7]), Err(MerkleError::IndexOob)));
+    }
+
+    #[test]
+    fn verify_stream_matches_verify_all() {
+        type Bundle = ProofBundle<String, Sha512_256Hasher>;
+        let sm = Sma::new((0..9).map(|i| format!("item-{i}")).collect());
+        let bundle = sm.proof_bundle(&[8, 0, 3]).unwrap();
+        let path = std::env::temp_dir().join(format!("sma_stream_{}.bin", std::process::id()));
+        bundle.save_to_file(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let root = sm.root();
+        assert!(Bundle::verify_stream(&bytes[..], &root).unwrap());
+        let other = Sha512_256Hasher::leaf(&0u8);
+        assert!(!Bundle::verify_stream(&bytes[..], &other).unwrap());
+
+        // A tampered entry stops the stream before the rest is read.
+        let mut swapped = bundle.clone();
+        swapped.entries[0].0 = "item-7".into();
+        swapped.save_to_file(&path).unwrap();
+        let mut tampered = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        tampered.truncate(tampered.len() - 1);
+        assert!(!Bundle::verify_stream(&tampered[..], &root).unwrap());
+
+        // A truncated but otherwise valid stream is a decoding error.
+        assert!(matches!(
+            Bundle::verify_stream(&bytes[..bytes.len() - 1], &root),
+            Err(MerkleError::Codec(_))
+        ));
+        assert!(matches!(
+            Bundle::verify_stream(&bytes[5..], &root),
+            Err(MerkleError::VersionMismatch { found: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,422 @@
+use serde::ser::{self, Impossible, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::digest::Sha256Digest;
+use crate::{HashError, MerkleHasher, StaticMerkleArray};
+
+/* ------------------------- Raw byte-string capture ------------------------- */
+
+/// Why an item could not be hashed as a byte string.
+#[derive(Debug)]
+struct NotBytes(String);
+
+impl fmt::Display for NotBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BytesHasher leaves must be byte strings: {}", self.0)
+    }
+}
+
+impl std::error::Error for NotBytes {}
+
+impl ser::Error for NotBytes {
+    fn custom<M: fmt::Display>(msg: M) -> Self {
+        NotBytes(msg.to_string())
+    }
+}
+
+/// Serializer that streams the bytes of a byte string straight into a hash,
+/// with no framing and no intermediate buffer.
+///
+/// Accepts serde byte strings (`serialize_bytes`, e.g. `Canonical<T>`),
+/// strings, and sequences/tuples of `u8` (how `Vec<u8>`, `&[u8]` and
+/// `[u8; N]` serialize), plus `(prefix, bytes)` pairs whose prefix is a
+/// `[u8; N]` (how salted trees hash `(salt, item)`), hashed as the prefix
+/// followed by the bytes. Anything else, including other nested sequences,
+/// is rejected, since concatenating it would be ambiguous.
+struct RawBytes<'a> {
+    hash: &'a mut Sha256,
+    state: State,
+}
+
+/// Where `RawBytes` is within the value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// The leaf itself.
+    Top,
+    /// Inside a sequence or array, where only single bytes are allowed.
+    Bytes,
+    /// A top-level 2-tuple before its first element: a `[u8; 2]` or a
+    /// `(prefix, bytes)` pair, told apart by that element.
+    Head,
+    /// Serializing that first element.
+    HeadElement,
+    /// The first element was a single byte, so the tuple is a `[u8; 2]`.
+    HeadByte,
+    /// The first element is a `[u8; N]` prefix.
+    Prefix,
+    /// Inside a pair, before the bytes after the prefix.
+    PairItem,
+    /// Inside a pair, after the bytes.
+    PairDone,
+}
+
+macro_rules! reject {
+    ($($method:ident($($arg:ty),*);)*) => {$(
+        fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Self::Error> {
+            Err(NotBytes(concat!("unsupported ", stringify!($method)).into()))
+        }
+    )*};
+}
+
+impl Serializer for &mut RawBytes<'_> {
+    type Ok = ();
+    type Error = NotBytes;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Impossible<(), NotBytes>;
+    type SerializeMap = Impossible<(), NotBytes>;
+    type SerializeStruct = Impossible<(), NotBytes>;
+    type SerializeStructVariant = Impossible<(), NotBytes>;
+
+    fn serialize_u8(self, v: u8) -> Result<(), NotBytes> {
+        match self.state {
+            State::Bytes | State::Prefix => {}
+            State::HeadElement => self.state = State::HeadByte,
+            _ => return Err(NotBytes("single byte outside a sequence".into())),
+        }
+        self.hash.update([v]);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), NotBytes> {
+        self.top_level("bytes")?;
+        self.hash.update(v);
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), NotBytes> {
+        self.top_level("str")?;
+        self.hash.update(v.as_bytes());
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), NotBytes> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, NotBytes> {
+        self.enter_seq()
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, NotBytes> {
+        match self.state {
+            State::Top if len == 2 => {
+                self.state = State::Head;
+                Ok(self)
+            }
+            State::HeadElement => {
+                self.state = State::Prefix;
+                Ok(self)
+            }
+            _ => self.enter_seq(),
+        }
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, NotBytes> {
+        self.enter_seq()
+    }
+
+    reject! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<(), NotBytes> {
+        Err(NotBytes("unsupported serialize_some".into()))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), NotBytes> {
+        Err(NotBytes("unsupported serialize_newtype_variant".into()))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, NotBytes> {
+        Err(NotBytes("unsupported serialize_tuple_variant".into()))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, NotBytes> {
+        Err(NotBytes("unsupported serialize_map".into()))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, NotBytes> {
+        Err(NotBytes("unsupported serialize_struct".into()))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, NotBytes> {
+        Err(NotBytes("unsupported serialize_struct_variant".into()))
+    }
+}
+
+impl<'a> RawBytes<'a> {
+    fn new(hash: &'a mut Sha256) -> Self {
+        RawBytes {
+            hash,
+            state: State::Top,
+        }
+    }
+
+    fn top_level(&self, what: &str) -> Result<(), NotBytes> {
+        if self.state != State::Top {
+            return Err(NotBytes(format!("{what} inside a sequence")));
+        }
+        Ok(())
+    }
+
+    fn enter_seq(&mut self) -> Result<&mut Self, NotBytes> {
+        self.top_level("sequence")?;
+        self.state = State::Bytes;
+        Ok(self)
+    }
+
+    /// One element of the sequence or tuple being serialized.
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NotBytes> {
+        let (sub, next) = match self.state {
+            State::Head => (State::HeadElement, None),
+            State::PairItem => (State::Top, Some(State::PairDone)),
+            State::Bytes | State::Prefix => return value.serialize(&mut *self),
+            _ => return Err(NotBytes("too many elements in a pair".into())),
+        };
+        let mut inner = RawBytes {
+            hash: &mut *self.hash,
+            state: sub,
+        };
+        value.serialize(&mut inner)?;
+        self.state = match (next, inner.state) {
+            (Some(next), _) => next,
+            (None, State::HeadByte) => State::Bytes,
+            (None, State::Prefix) => State::PairItem,
+            _ => return Err(NotBytes("pair prefix must be a [u8; N]".into())),
+        };
+        Ok(())
+    }
+}
+
+macro_rules! seq_of_bytes {
+    ($($trait:ident :: $method:ident),*) => {$(
+        impl ser::$trait for &mut RawBytes<'_> {
+            type Ok = ();
+            type Error = NotBytes;
+
+            fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NotBytes> {
+                self.element(value)
+            }
+
+            fn end(self) -> Result<(), NotBytes> {
+                Ok(())
+            }
+        }
+    )*};
+}
+
+seq_of_bytes!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field
+);
+
+/* ----------------------------- The Hasher --------------------------------- */
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+
+/// SHA-256 hasher over raw byte-string leaves, without `bincode` framing.
+///
+/// A leaf is `H(0x00 || bytes)` and a node `H(0x01 || left || right)`, the
+/// RFC 6962 convention, so leaf hashes match tools that hash the bytes
+/// themselves rather than a length-prefixed encoding. (Roots match RFC 6962
+/// for power-of-two lengths; other lengths pad by duplication here.) The
+/// bytes are streamed into the hash straight from `serialize`, without
+/// copying them into an encoding first.
+///
+/// Leaves must serialize as a byte string: `Vec<u8>`, `&[u8]`, `[u8; N]`,
+/// `String` / `&str`, or `leaf::Canonical<T>`. `try_leaf` rejects anything
+/// else, and `leaf` panics on it.
+///
+/// Salted and position-bound trees prefix the raw bytes instead of framing
+/// them: a salted leaf is `H(0x00 || salt || bytes)`, a position-bound one
+/// `H(0x00 || index || bytes)` with the index as 8 big-endian bytes, and a
+/// leaf that is both `H(0x00 || index || salt || bytes)`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BytesHasher;
+
+/// `H(0x00 || prefix || bytes)`, streaming `item`'s bytes into the hash.
+fn raw_leaf<T: Serialize + ?Sized>(prefix: &[u8], item: &T) -> Result<Sha256Digest, HashError> {
+    let mut hash = Sha256::new();
+    hash.update([LEAF_TAG]);
+    hash.update(prefix);
+    item.serialize(&mut RawBytes::new(&mut hash))?;
+    Ok(Sha256Digest(hash.finalize().into()))
+}
+
+impl MerkleHasher for BytesHasher {
+    type Digest = Sha256Digest;
+
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        Self::try_leaf(item).expect("leaf is not a byte string")
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        raw_leaf(&[], item)
+    }
+
+    fn leaf_with_index<T: Serialize>(item: &T, index: usize) -> Self::Digest {
+        Self::try_leaf_with_index(item, index).expect("leaf is not a byte string")
+    }
+
+    /// `H(0x00 || index || bytes)`, the index as a big-endian `u64`.
+    fn try_leaf_with_index<T: Serialize>(
+        item: &T,
+        index: usize,
+    ) -> Result<Self::Digest, HashError> {
+        raw_leaf(&(index as u64).to_be_bytes(), item)
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        let mut h = Sha256::new();
+        h.update([NODE_TAG]);
+        h.update(left);
+        h.update(right);
+        Sha256Digest(h.finalize().into())
+    }
+
+    /// `H()` with no tag, which is neither a leaf nor a node hash (RFC 6962's
+    /// empty-tree hash). The default, `leaf(&())`, is not a byte string.
+    fn empty_leaf() -> Self::Digest {
+        Sha256Digest(Sha256::digest([]).into())
+    }
+}
+
+/* ------------------------------- Type alias -------------------------------- */
+
+pub type BytesMerkle = StaticMerkleArray<Vec<u8>, BytesHasher>;
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leaf::{Canonical, MerkleLeaf};
+    use crate::verify_value_with_proof;
+
+    fn tagged(bytes: &[u8]) -> Sha256Digest {
+        let mut h = Sha256::new();
+        h.update([LEAF_TAG]);
+        h.update(bytes);
+        Sha256Digest(h.finalize().into())
+    }
+
+    #[test]
+    fn byte_strings_hash_without_framing() {
+        let expected = tagged(b"abc");
+        assert_eq!(BytesHasher::leaf(&b"abc".to_vec()), expected);
+        assert_eq!(BytesHasher::leaf(&&b"abc"[..]), expected);
+        assert_eq!(BytesHasher::leaf(b"abc"), expected);
+        assert_eq!(BytesHasher::leaf(&"abc"), expected);
+        assert_eq!(
+            BytesHasher::leaf(&Canonical(7u32)),
+            tagged(&7u32.leaf_bytes())
+        );
+        // RFC 6962 leaf hash of the empty string.
+        assert_eq!(
+            hex::encode(BytesHasher::leaf(&Vec::<u8>::new())),
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"
+        );
+
+        assert_eq!(BytesHasher::leaf(&[1u8, 2]), tagged(&[1, 2]));
+
+        assert!(BytesHasher::try_leaf(&7u64).is_err());
+        assert!(BytesHasher::try_leaf(&vec![vec![1u8], vec![2]]).is_err());
+        assert!(BytesHasher::try_leaf(&vec![1u16, 2]).is_err());
+        // Only a fixed-size prefix keeps a pair unambiguous.
+        assert!(BytesHasher::try_leaf(&(vec![1u8], vec![2u8])).is_err());
+        assert!(BytesHasher::try_leaf(&([1u8; 2], vec![2u8], vec![3u8])).is_err());
+    }
+
+    #[test]
+    fn salted_and_position_bound_leaves_prefix_the_bytes() {
+        let items: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 3]).collect();
+        let salt = [9u8; 32];
+
+        let salted = BytesMerkle::new_salted(items.clone(), salt);
+        let bound = BytesMerkle::new_position_bound(items.clone());
+        for (i, item) in items.iter().enumerate() {
+            let proof = salted.prove_index(i).unwrap();
+            assert_eq!(proof.leaf, tagged(&[&salt[..], item].concat()));
+            assert!(verify_value_with_proof(item, &proof));
+
+            let proof = bound.prove_index(i).unwrap();
+            let index = (i as u64).to_be_bytes();
+            assert_eq!(proof.leaf, tagged(&[&index[..], item].concat()));
+            assert!(verify_value_with_proof(item, &proof));
+        }
+        assert!(salted.verify_self_proofs());
+        assert!(bound.verify_self_proofs());
+        assert_eq!(salted.positions_of(&items[3]), vec![3]);
+        assert_eq!(
+            BytesHasher::leaf_with_index(&(salt, &items[1]), 1),
+            tagged(&[&1u64.to_be_bytes()[..], &salt, &items[1]].concat())
+        );
+    }
+
+    #[test]
+    fn bytes_tree_proofs_verify() {
+        let items: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; i as usize]).collect();
+        let sm = BytesMerkle::new(items.clone());
+        for (i, item) in items.iter().enumerate() {
+            let proof = sm.prove_index(i).unwrap();
+            assert_eq!(proof.leaf, tagged(item));
+            assert!(verify_value_with_proof(item, &proof));
+        }
+        assert_ne!(BytesHasher::empty_leaf(), tagged(b""));
+    }
+}
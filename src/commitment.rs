@@ -0,0 +1,148 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::json_hasher::CanonicalJsonSha256Hasher;
+#[cfg(feature = "mimc")]
+use crate::mimc_bn254_hasher::MiMCBn254RuleHasher;
+#[cfg(feature = "mimc")]
+use crate::pedersen::PedersenBn254Hasher;
+#[cfg(feature = "mimc")]
+use crate::poseidon2::Poseidon2Bn254Hasher;
+use crate::sha3_hasher::{Keccak256Hasher, Sha3_256Hasher};
+use crate::sha_hasher::{Be64Hasher, Sha512_256Hasher};
+use crate::{MerkleHasher, StaticMerkleArray};
+
+/* -------------------------------------------------------------------------
+Algorithm-tagged commitments
+------------------------------------------------------------------------- */
+
+/// A root together with the bundled hasher that produced it.
+///
+/// Serde encodes the variant tag, so commitments from different hashers can
+/// share storage and still deserialize unambiguously, which bare
+/// `[u8; 32]` roots can't. The BN254 variants exist with or without the
+/// `mimc` feature, so stored commitments decode the same either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Commitment {
+    /// `CanonicalJsonSha256Hasher`.
+    Sha256Json([u8; 32]),
+    /// `Sha512_256Hasher`.
+    Sha512_256([u8; 32]),
+    /// `Be64Hasher`.
+    Sha512_256Be64([u8; 32]),
+    /// `MiMCBn254RuleHasher` (little-endian `Fr`).
+    MiMC([u8; 32]),
+    /// `Poseidon2Bn254Hasher` (little-endian `Fr`).
+    Poseidon2([u8; 32]),
+    /// `PedersenBn254Hasher` (compressed G1 point).
+    Pedersen([u8; 32]),
+    /// `Sha3_256Hasher`.
+    Sha3_256([u8; 32]),
+    /// `Keccak256Hasher`.
+    Keccak256([u8; 32]),
+}
+
+impl Commitment {
+    /// Stable name of the hashing scheme.
+    pub fn algorithm(&self) -> &str {
+        match self {
+            Commitment::Sha256Json(_) => "sha256-canonical-json",
+            Commitment::Sha512_256(_) => "sha512-256",
+            Commitment::Sha512_256Be64(_) => "sha512-256-be64",
+            Commitment::MiMC(_) => "mimc-bn254",
+            Commitment::Poseidon2(_) => "poseidon2-bn254",
+            Commitment::Pedersen(_) => "pedersen-bn254",
+            Commitment::Sha3_256(_) => Sha3_256Hasher::NAME,
+            Commitment::Keccak256(_) => Keccak256Hasher::NAME,
+        }
+    }
+
+    /// The root digest bytes, as stored by the hasher.
+    pub fn root_bytes(&self) -> &[u8] {
+        match self {
+            Commitment::Sha256Json(r)
+            | Commitment::Sha512_256(r)
+            | Commitment::Sha512_256Be64(r)
+            | Commitment::MiMC(r)
+            | Commitment::Poseidon2(r)
+            | Commitment::Pedersen(r)
+            | Commitment::Sha3_256(r)
+            | Commitment::Keccak256(r) => r,
+        }
+    }
+}
+
+/// Bundled hashers that can tag their roots as a `Commitment`.
+pub trait CommitmentHasher: MerkleHasher {
+    /// Wrap `root` in this hasher's variant.
+    fn commitment(root: &Self::Digest) -> Commitment;
+}
+
+macro_rules! commitment_hasher {
+    ($($hasher:ty => $variant:ident),* $(,)?) => {$(
+        impl CommitmentHasher for $hasher {
+            fn commitment(root: &Self::Digest) -> Commitment {
+                Commitment::$variant(root.0)
+            }
+        }
+    )*};
+}
+
+commitment_hasher!(
+    CanonicalJsonSha256Hasher => Sha256Json,
+    Sha512_256Hasher => Sha512_256,
+    Be64Hasher => Sha512_256Be64,
+    Sha3_256Hasher => Sha3_256,
+    Keccak256Hasher => Keccak256,
+);
+
+#[cfg(feature = "mimc")]
+commitment_hasher!(
+    MiMCBn254RuleHasher => MiMC,
+    Poseidon2Bn254Hasher => Poseidon2,
+    PedersenBn254Hasher => Pedersen,
+);
+
+impl<T, H> StaticMerkleArray<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: CommitmentHasher,
+{
+    /// The root tagged with its hashing algorithm.
+    pub fn commitment(&self) -> Commitment {
+        H::commitment(&self.root())
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commitments_keep_their_algorithm() {
+        let items: Vec<u64> = (0..5).collect();
+        let a = StaticMerkleArray::<u64, Sha512_256Hasher>::new(items.clone()).commitment();
+        let b = StaticMerkleArray::<u64, Be64Hasher>::new(items).commitment();
+        assert_eq!(a.algorithm(), "sha512-256");
+        assert_eq!(b.algorithm(), "sha512-256-be64");
+
+        let stored = bincode::serialize(&vec![a, b]).unwrap();
+        let back: Vec<Commitment> = bincode::deserialize(&stored).unwrap();
+        assert_eq!(back, vec![a, b]);
+        assert_ne!(back[0].root_bytes(), back[1].root_bytes());
+    }
+
+    #[test]
+    fn sha3_commitment_does_not_decode_as_keccak() {
+        let items: Vec<u64> = (0..5).collect();
+        let sha3 = StaticMerkleArray::<u64, Sha3_256Hasher>::new(items.clone()).commitment();
+        let keccak = StaticMerkleArray::<u64, Keccak256Hasher>::new(items).commitment();
+        assert_eq!(sha3.algorithm(), "sha3-256");
+        assert_eq!(keccak.algorithm(), "keccak256");
+
+        let back: Commitment = bincode::deserialize(&bincode::serialize(&sha3).unwrap()).unwrap();
+        assert!(matches!(back, Commitment::Sha3_256(_)));
+        assert_ne!(back, keccak);
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::incremental::IncrementalRoot;
+use crate::{depth_for_len, level_width, MerkleError, MerkleHasher, StaticMerkleArray};
+
+/* -------------------------------------------------------------------------
+Consistency Proof
+------------------------------------------------------------------------- */
+
+/// Proof that the tree over the first `old_len` leaves is a prefix of the
+/// tree over `new_len` leaves (the append-only log check of RFC 6962, for
+/// this crate's duplicate-last padding).
+///
+/// The first `old_len` leaves split into full aligned subtrees, one per set
+/// bit of `old_len`; those "peaks" are nodes of both trees. The verifier
+/// rebuilds the old root from them, then folds them up the new tree with
+/// `siblings`, which holds only the nodes it can neither compute nor get by
+/// padding.
+///
+/// With the default identity `finalize_root`, a tree whose last leaf repeats
+/// has the same root as the tree without that repeat, so `new_len` is only
+/// pinned down by hashers that bind the length into the root.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct ConsistencyProof<H: MerkleHasher> {
+    /// Roots of the old tree's full subtrees, largest (leftmost) first.
+    pub old_peaks: Vec<H::Digest>,
+    /// Extra nodes of the new tree, in the order `verify` consumes them
+    /// (bottom to top, left to right within a level).
+    pub siblings: Vec<H::Digest>,
+}
+
+impl<H: MerkleHasher> ConsistencyProof<H> {
+    /// Check that `old_root` (over `old_len` leaves) is a prefix commitment
+    /// of `new_root` (over `new_len` leaves).
+    pub fn verify(
+        &self,
+        old_root: &H::Digest,
+        old_len: usize,
+        new_root: &H::Digest,
+        new_len: usize,
+    ) -> bool {
+        if old_len == 0 || old_len > new_len {
+            return false;
+        }
+        let old = IncrementalRoot::<H>::from_peaks(old_len, &self.old_peaks)
+            .and_then(|inc| inc.current_root());
+        if old.as_ref() != Some(old_root) {
+            return false;
+        }
+
+        let peaks = peak_positions(old_len)
+            .into_iter()
+            .zip(self.old_peaks.iter().copied());
+        let mut sibs = self.siblings.iter();
+        let top = fold_to_top::<H>(new_len, peaks.collect(), |_, _| sibs.next().copied());
+        sibs.next().is_none() && top.is_some_and(|t| H::finalize_root(&t, new_len) == *new_root)
+    }
+}
+
+/// `(level, index)` of each full subtree covering `0..len`, largest first.
+fn peak_positions(len: usize) -> Vec<(usize, usize)> {
+    let mut start = 0;
+    (0..usize::BITS as usize)
+        .rev()
+        .filter(|h| len >> h & 1 == 1)
+        .map(|h| {
+            let pos = (h, start >> h);
+            start += 1 << h;
+            pos
+        })
+        .collect()
+}
+
+/// Fold `peaks` (`((level, index), digest)`, largest first) up a tree of
+/// `len` leaves to its top node, asking `sibling(level, index)` for any node
+/// that is neither known nor a padding duplicate.
+fn fold_to_top<H: MerkleHasher>(
+    len: usize,
+    mut peaks: Vec<((usize, usize), H::Digest)>,
+    mut sibling: impl FnMut(usize, usize) -> Option<H::Digest>,
+) -> Option<H::Digest> {
+    let depth = depth_for_len(len);
+    let mut known: Vec<(usize, H::Digest)> = Vec::new();
+    for level in 0..=depth {
+        // Peaks come largest first, so this level's (if any) is at the end,
+        // left of everything computed from the smaller ones.
+        if let Some(&((h, i), d)) = peaks.last() {
+            if h == level {
+                known.insert(0, (i, d));
+                peaks.pop();
+            }
+        }
+        if level == depth {
+            break;
+        }
+        let width = level_width(len, level);
+        let mut next = Vec::with_capacity(known.len());
+        let mut k = 0;
+        while k < known.len() {
+            let (i, d) = known[k];
+            let parent = if i % 2 == 1 {
+                H::node(&sibling(level, i - 1)?, &d)
+            } else if k + 1 < known.len() && known[k + 1].0 == i + 1 {
+                k += 1;
+                H::node(&d, &known[k].1)
+            } else if i + 1 >= width {
+                H::node(&d, &d)
+            } else {
+                H::node(&d, &sibling(level, i + 1)?)
+            };
+            next.push((i / 2, parent));
+            k += 1;
+        }
+        known = next;
+    }
+    match (peaks.is_empty(), known.as_slice()) {
+        (true, [(0, top)]) => Some(*top),
+        _ => None,
+    }
+}
+
+impl<T, H> StaticMerkleArray<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    /// Prove that the tree over the first `old_len` items is a prefix of this
+    /// one (see `ConsistencyProof`).
+    ///
+    /// Returns `MerkleError::IndexOob` unless `1 <= old_len <= leaf_count()`.
+    pub fn consistency_proof(&self, old_len: usize) -> Result<ConsistencyProof<H>, MerkleError> {
+        if old_len == 0 || old_len > self.leaf_count() {
+            return Err(MerkleError::IndexOob);
+        }
+        let peaks: Vec<_> = peak_positions(old_len)
+            .into_iter()
+            .map(|(h, i)| ((h, i), self.levels[h][i]))
+            .collect();
+        let old_peaks = peaks.iter().map(|(_, d)| *d).collect();
+        let mut siblings = Vec::new();
+        fold_to_top::<H>(self.leaf_count(), peaks, |level, i| {
+            siblings.push(self.levels[level][i]);
+            Some(self.levels[level][i])
+        })
+        .expect("peaks are nodes of this tree");
+        Ok(ConsistencyProof {
+            old_peaks,
+            siblings,
+        })
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+
+    type Sma = StaticMerkleArray<u64, Sha512_256Hasher>;
+
+    #[test]
+    fn every_prefix_is_consistent() {
+        for new_len in 1..=21u64 {
+            let tree = Sma::new((0..new_len).collect());
+            let roots = tree.roots_of_prefixes();
+            for old_len in 1..=new_len as usize {
+                let proof = tree.consistency_proof(old_len).unwrap();
+                assert!(
+                    proof.verify(&roots[old_len - 1], old_len, &tree.root(), tree.len()),
+                    "{old_len} -> {new_len}"
+                );
+                assert!(proof.siblings.len() <= 2 * depth_for_len(tree.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn forged_histories_fail() {
+        let tree = Sma::new((0..13).collect());
+        let roots = tree.roots_of_prefixes();
+        let proof = tree.consistency_proof(6).unwrap();
+        assert!(proof.verify(&roots[5], 6, &tree.root(), 13));
+        assert!(!proof.verify(&roots[4], 6, &tree.root(), 13));
+        assert!(!proof.verify(&roots[5], 6, &tree.root(), 17));
+        assert!(!proof.verify(&roots[5], 14, &tree.root(), 13));
+
+        let rewritten = Sma::new((0..13).map(|x| if x == 2 { 99 } else { x }).collect());
+        let bad = rewritten.consistency_proof(6).unwrap();
+        assert!(!bad.verify(&roots[5], 6, &rewritten.root(), 13));
+
+        let mut tampered = proof;
+        tampered.siblings[0] = tampered.old_peaks[0];
+        assert!(!tampered.verify(&roots[5], 6, &tree.root(), 13));
+        assert!(matches!(
+            tree.consistency_proof(14),
+            Err(MerkleError::IndexOob)
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+use serde::de::{Error as _, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::Deref;
+
+/* -------------------------------------------------------------------------
+Strongly-typed digests
+------------------------------------------------------------------------- */
+
+// Each hasher gets its own 32-byte digest type, so a root from one hasher
+// can't be passed where another's is expected. They serialize exactly like
+// the wrapped `[u8; 32]` and convert to and from it freely.
+macro_rules! digest_newtype {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(pub [u8; 32]);
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({})", stringify!($name), hex::encode(self.0))
+            }
+        }
+
+        impl Deref for $name {
+            type Target = [u8; 32];
+
+            fn deref(&self) -> &[u8; 32] {
+                &self.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl From<[u8; 32]> for $name {
+            fn from(bytes: [u8; 32]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl From<$name> for [u8; 32] {
+            fn from(digest: $name) -> Self {
+                digest.0
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = std::array::TryFromSliceError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                Ok(Self(bytes.try_into()?))
+            }
+        }
+    };
+}
+
+digest_newtype!(
+    /// Digest of `CanonicalJsonSha256Hasher` and `BytesHasher`.
+    Sha256Digest
+);
+digest_newtype!(
+    /// Digest of `Sha512_256Hasher` and `Be64Hasher`.
+    Sha512_256Digest
+);
+digest_newtype!(
+    /// Digest of `Sha3_256Hasher`.
+    Sha3_256Digest
+);
+digest_newtype!(
+    /// Digest of `Keccak256Hasher`.
+    Keccak256Digest
+);
+digest_newtype!(
+    /// Digest of `MiMCBn254RuleHasher`: a BN254 `Fr` element, little-endian.
+    MiMCDigest
+);
+digest_newtype!(
+    /// Digest of `PoseidonMerkleBn254Hasher`: a BN254 `Fr` element, little-endian.
+    PoseidonDigest
+);
+digest_newtype!(
+    /// Digest of `Poseidon2Bn254Hasher`: a BN254 `Fr` element, little-endian.
+    Poseidon2Digest
+);
+digest_newtype!(
+    /// Digest of `PedersenBn254Hasher`: a compressed BN254 G1 point.
+    PedersenDigest
+);
+
+/* -------------------------------------------------------------------------
+Truncated digests
+------------------------------------------------------------------------- */
+
+/// Digest of `TruncatedHasher<_, N>`: the first `N` bytes of the wrapped
+/// hasher's output. Serializes exactly like `[u8; N]`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TruncatedDigest<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Default for TruncatedDigest<N> {
+    fn default() -> Self {
+        Self([0u8; N])
+    }
+}
+
+impl<const N: usize> fmt::Debug for TruncatedDigest<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TruncatedDigest<{N}>({})", hex::encode(self.0))
+    }
+}
+
+impl<const N: usize> Deref for TruncatedDigest<N> {
+    type Target = [u8; N];
+
+    fn deref(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for TruncatedDigest<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for TruncatedDigest<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for TruncatedDigest<N> {
+    type Error = std::array::TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(bytes.try_into()?))
+    }
+}
+
+// serde only implements arrays up to length 32 by hand, not for a generic
+// `N`, so encode as an `N`-tuple of bytes the way it does.
+impl<const N: usize> Serialize for TruncatedDigest<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+        let mut tup = serializer.serialize_tuple(N)?;
+        for b in &self.0 {
+            tup.serialize_element(b)?;
+        }
+        tup.end()
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for TruncatedDigest<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor<const N: usize>;
+
+        impl<'de, const N: usize> Visitor<'de> for BytesVisitor<N> {
+            type Value = TruncatedDigest<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "an array of {N} bytes")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut out = [0u8; N];
+                for (i, b) in out.iter_mut().enumerate() {
+                    *b = seq
+                        .next_element()?
+                        .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+                }
+                Ok(TruncatedDigest(out))
+            }
+        }
+
+        deserializer.deserialize_tuple(N, BytesVisitor::<N>)
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newtypes_encode_like_raw_bytes() {
+        let raw = [7u8; 32];
+        let d = MiMCDigest::from(raw);
+        assert_eq!(
+            bincode::serialize(&d).unwrap(),
+            bincode::serialize(&raw).unwrap()
+        );
+        assert_eq!(<[u8; 32]>::from(d), raw);
+        assert_eq!(d.as_ref(), &raw[..]);
+        assert!(Sha256Digest::try_from(&raw[1..]).is_err());
+        assert_eq!(
+            format!("{d:?}"),
+            format!("MiMCDigest({})", hex::encode(raw))
+        );
+
+        let short = TruncatedDigest([9u8; 16]);
+        let bytes = bincode::serialize(&short).unwrap();
+        assert_eq!(bytes, bincode::serialize(&[9u8; 16]).unwrap());
+        assert_eq!(
+            bincode::deserialize::<TruncatedDigest<16>>(&bytes).unwrap(),
+            short
+        );
+    }
+}
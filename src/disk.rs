@@ -0,0 +1,254 @@
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::{io_err, MerkleError, MerkleHasher, MerkleProof, Side};
+
+/* -------------------------------------------------------------------------
+Disk-backed builder
+------------------------------------------------------------------------- */
+
+/// Builds a Merkle tree whose levels live in files rather than in memory.
+///
+/// Leaves are hashed straight from an iterator and each level is written to
+/// `dir/level_<k>.bin` as it is computed; only a pair of nodes is held in
+/// memory at a time. The resulting `DiskMerkleTree` reads nodes back on
+/// demand, so the commitment size is bounded by disk, not RAM.
+///
+/// The tree shape (duplicate padding of odd levels) and the proofs are
+/// identical to `StaticMerkleArray` over the same items. The items themselves
+/// are not stored, only their leaf hashes.
+///
+/// The result is its own type, not a `StaticMerkleArray` with mapped
+/// levels: that type keeps its items and levels in memory, so only the
+/// root, `len` and `prove_index` are available here, reading the level
+/// files through ordinary buffered I/O rather than a memory map.
+#[derive(Debug, Clone)]
+pub struct DiskBackedBuilder {
+    dir: PathBuf,
+}
+
+impl DiskBackedBuilder {
+    /// Use `dir` (created if missing) for the level files.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Stream `items` into a disk-backed tree.
+    ///
+    /// A leaf or node that fails to hash is reported as `MerkleError::Hasher`.
+    pub fn build<T, H, I>(&self, items: I) -> Result<DiskMerkleTree<H>, MerkleError>
+    where
+        T: Serialize,
+        H: MerkleHasher,
+        I: IntoIterator<Item = T>,
+    {
+        fs::create_dir_all(&self.dir).map_err(io_err(&self.dir))?;
+
+        // Level 0: hash leaves as they arrive.
+        let mut out_path = level_path(&self.dir, 0);
+        let mut out = BufWriter::new(File::create(&out_path).map_err(io_err(&out_path))?);
+        let mut len = 0usize;
+        let mut last = None;
+        let mut digest_len = 0;
+        for item in items {
+            let leaf = H::try_leaf(&item).map_err(MerkleError::Hasher)?;
+            let bytes = bincode::serialize(&leaf)?;
+            digest_len = bytes.len();
+            out.write_all(&bytes).map_err(io_err(&out_path))?;
+            last = Some(leaf);
+            len += 1;
+        }
+        let Some(mut last) = last else {
+            return Err(MerkleError::EmptyInput);
+        };
+
+        let mut level_lens = Vec::new();
+        let mut cur_len = len;
+        let mut level = 0;
+        while cur_len > 1 {
+            if cur_len % 2 == 1 {
+                out.write_all(&bincode::serialize(&last)?)
+                    .map_err(io_err(&out_path))?;
+                cur_len += 1;
+            }
+            out.flush().map_err(io_err(&out_path))?;
+            level_lens.push(cur_len);
+
+            // Combine pairs of the level just written into the next one.
+            let in_path = out_path;
+            let mut input = BufReader::new(File::open(&in_path).map_err(io_err(&in_path))?);
+            out_path = level_path(&self.dir, level + 1);
+            out = BufWriter::new(File::create(&out_path).map_err(io_err(&out_path))?);
+            let mut buf = vec![0u8; digest_len];
+            for _ in 0..cur_len / 2 {
+                input.read_exact(&mut buf).map_err(io_err(&in_path))?;
+                let left: H::Digest = bincode::deserialize(&buf)?;
+                input.read_exact(&mut buf).map_err(io_err(&in_path))?;
+                let right: H::Digest = bincode::deserialize(&buf)?;
+                last = H::try_node(&left, &right).map_err(MerkleError::Hasher)?;
+                out.write_all(&bincode::serialize(&last)?)
+                    .map_err(io_err(&out_path))?;
+            }
+            cur_len /= 2;
+            level += 1;
+        }
+        out.flush().map_err(io_err(&out_path))?;
+        level_lens.push(cur_len);
+
+        Ok(DiskMerkleTree {
+            dir: self.dir.clone(),
+            level_lens,
+            len,
+            digest_len,
+            root: H::finalize_root(&last, len),
+            _hasher: PhantomData,
+        })
+    }
+}
+
+fn level_path(dir: &Path, level: usize) -> PathBuf {
+    dir.join(format!("level_{level}.bin"))
+}
+
+/* -------------------------------------------------------------------------
+Disk Merkle Tree
+------------------------------------------------------------------------- */
+
+/// A Merkle tree whose levels are read from files on demand.
+#[derive(Debug, Clone)]
+pub struct DiskMerkleTree<H: MerkleHasher> {
+    dir: PathBuf,
+    /// Stored (padded) length of each level, bottom-up.
+    level_lens: Vec<usize>,
+    len: usize,
+    digest_len: usize,
+    /// Finalized root (see `MerkleHasher::finalize_root`).
+    root: H::Digest,
+    _hasher: PhantomData<H>,
+}
+
+impl<H: MerkleHasher> DiskMerkleTree<H> {
+    /// Root commitment.
+    pub fn root(&self) -> H::Digest {
+        self.root
+    }
+
+    /// Number of leaves.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the tree empty? (Never true for a successfully built tree.)
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Build a proof of membership for a given index.
+    pub fn prove_index(&self, index: usize) -> Result<MerkleProof<H>, MerkleError> {
+        if index >= self.len {
+            return Err(MerkleError::IndexOob);
+        }
+        let leaf = self.read_node(0, index)?;
+        let mut siblings = Vec::with_capacity(self.level_lens.len() - 1);
+        let mut i = index;
+        for level in 0..self.level_lens.len() - 1 {
+            let is_right = i % 2 == 1;
+            let sib_idx = if is_right { i - 1 } else { i + 1 };
+            let side = if is_right { Side::Left } else { Side::Right };
+            siblings.push((self.read_node(level, sib_idx)?, side));
+            i /= 2;
+        }
+
+        Ok(MerkleProof {
+            index,
+            tree_len: self.len,
+            siblings,
+            root: self.root,
+            leaf,
+            salt: None,
+            position_bound: false,
+        })
+    }
+
+    /// Remove the level files.
+    pub fn remove_files(self) -> Result<(), MerkleError> {
+        for level in 0..self.level_lens.len() {
+            let path = level_path(&self.dir, level);
+            fs::remove_file(&path).map_err(io_err(&path))?;
+        }
+        Ok(())
+    }
+
+    fn read_node(&self, level: usize, index: usize) -> Result<H::Digest, MerkleError> {
+        let path = level_path(&self.dir, level);
+        let mut file = File::open(&path).map_err(io_err(&path))?;
+        file.seek(SeekFrom::Start((index * self.digest_len) as u64))
+            .map_err(io_err(&path))?;
+        let mut buf = vec![0u8; self.digest_len];
+        file.read_exact(&mut buf).map_err(io_err(&path))?;
+        Ok(bincode::deserialize(&buf)?)
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes_hasher::BytesHasher;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::{verify_value_with_proof, HashError, StaticMerkleArray};
+
+    /// Hashes leaves, but its backend refuses every node.
+    struct NoNodes;
+
+    impl MerkleHasher for NoNodes {
+        type Digest = <Sha512_256Hasher as MerkleHasher>::Digest;
+
+        fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+            Sha512_256Hasher::leaf(item)
+        }
+
+        fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+            Self::try_node(left, right).expect("backend failure")
+        }
+
+        fn try_node(_: &Self::Digest, _: &Self::Digest) -> Result<Self::Digest, HashError> {
+            Err("backend unavailable".into())
+        }
+    }
+
+    #[test]
+    fn disk_tree_matches_in_memory_tree() {
+        let dir = std::env::temp_dir().join(format!("sma_disk_{}", std::process::id()));
+        for n in [1usize, 2, 7, 13, 16] {
+            let items: Vec<u64> = (0..n as u64).collect();
+            let disk: DiskMerkleTree<Sha512_256Hasher> = DiskBackedBuilder::new(&dir)
+                .build(items.iter().copied())
+                .unwrap();
+            let sm: StaticMerkleArray<u64, Sha512_256Hasher> =
+                StaticMerkleArray::new(items.clone());
+            assert_eq!(disk.root(), sm.root(), "n = {n}");
+
+            for (i, v) in items.iter().enumerate() {
+                let proof = disk.prove_index(i).unwrap();
+                assert_eq!(proof.siblings, sm.prove_index(i).unwrap().siblings);
+                assert!(verify_value_with_proof(v, &proof));
+            }
+            assert!(matches!(disk.prove_index(n), Err(MerkleError::IndexOob)));
+            disk.remove_files().unwrap();
+        }
+
+        let empty = DiskBackedBuilder::new(&dir).build::<u64, Sha512_256Hasher, _>(Vec::new());
+        assert!(matches!(empty, Err(MerkleError::EmptyInput)));
+        // Leaves that don't hash are an error, not a panic.
+        let unhashable = DiskBackedBuilder::new(&dir).build::<u64, BytesHasher, _>(vec![1, 2]);
+        assert!(matches!(unhashable, Err(MerkleError::Hasher(_))));
+        let no_nodes = DiskBackedBuilder::new(&dir).build::<u64, NoNodes, _>(vec![1, 2]);
+        assert!(matches!(no_nodes, Err(MerkleError::Hasher(_))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
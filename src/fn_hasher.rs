@@ -0,0 +1,117 @@
+use serde::Serialize;
+use std::marker::PhantomData;
+
+use crate::{HashError, MerkleHasher};
+
+/* -------------------------------------------------------------------------
+Hashers from plain functions
+------------------------------------------------------------------------- */
+
+/// A pair of plain hash functions over bytes, for `FnHasher`.
+///
+/// `MerkleHasher` has no `&self`, so the functions live on a type rather than
+/// in a value; `fn_hasher!` writes that type from two closures.
+pub trait HashFns {
+    /// Hash a leaf's `bincode` encoding.
+    fn leaf(bytes: &[u8]) -> [u8; 32];
+    /// Hash two child digests.
+    fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32];
+}
+
+/// `MerkleHasher` built from a `HashFns` type, for prototyping a hash without
+/// writing out the trait: a leaf is `F::leaf(bincode(item))`, a node
+/// `F::node(left, right)`, and digests are plain `[u8; 32]`.
+///
+/// ```
+/// use sha2::{Digest, Sha256};
+/// use static_merkle_array::{fn_hasher, fn_hasher::FnHasher, StaticMerkleArray};
+///
+/// fn_hasher!(Plain,
+///     leaf = |bytes| Sha256::digest(bytes).into(),
+///     node = |l, r| Sha256::new().chain_update(l).chain_update(r).finalize().into(),
+/// );
+///
+/// let tree = StaticMerkleArray::<u64, FnHasher<Plain>>::new(vec![1, 2, 3]);
+/// assert!(tree.prove_index(2).unwrap().verify());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FnHasher<F>(PhantomData<F>);
+
+impl<F: HashFns> MerkleHasher for FnHasher<F> {
+    type Digest = [u8; 32];
+
+    /// Panics if `item` fails to serialize; `try_leaf` returns the error.
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        Self::try_leaf(item).expect("bincode serialize")
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        Ok(F::leaf(&bincode::serialize(item)?))
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        F::node(left, right)
+    }
+}
+
+/// Define a unit struct implementing `HashFns` from two non-capturing
+/// closures (or function paths), for use as `FnHasher<Name>`.
+///
+/// `leaf` takes `&[u8]` and `node` takes `(&[u8; 32], &[u8; 32])`; both
+/// return `[u8; 32]`.
+#[macro_export]
+macro_rules! fn_hasher {
+    ($(#[$meta:meta])* $vis:vis $name:ident, leaf = $leaf:expr, node = $node:expr $(,)?) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default)]
+        $vis struct $name;
+
+        impl $crate::fn_hasher::HashFns for $name {
+            fn leaf(bytes: &[u8]) -> [u8; 32] {
+                let f: fn(&[u8]) -> [u8; 32] = $leaf;
+                f(bytes)
+            }
+
+            fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+                let f: fn(&[u8; 32], &[u8; 32]) -> [u8; 32] = $node;
+                f(left, right)
+            }
+        }
+    };
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::StaticMerkleArray;
+    use sha2::{Digest, Sha512_256};
+
+    fn_hasher!(
+        TaggedSha,
+        leaf = |bytes| Sha512_256::new()
+            .chain_update([0u8])
+            .chain_update(bytes)
+            .finalize()
+            .into(),
+        node = |l, r| {
+            Sha512_256::new()
+                .chain_update([1u8])
+                .chain_update(l)
+                .chain_update(r)
+                .finalize()
+                .into()
+        },
+    );
+
+    #[test]
+    fn closures_reproduce_a_bundled_hasher() {
+        let arr: Vec<u64> = (0..10).collect();
+        let f: StaticMerkleArray<u64, FnHasher<TaggedSha>> = StaticMerkleArray::new(arr.clone());
+        let s: StaticMerkleArray<u64, Sha512_256Hasher> = StaticMerkleArray::new(arr);
+        assert_eq!(f.root(), *s.root());
+        assert!(f.prove_index(9).unwrap().verify());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{MerkleError, MerkleHasher, MerkleProof, MultiProof, StaticMerkleArray};
+
+/* -------------------------------------------------------------------------
+Pre-hashed key/value pairs
+------------------------------------------------------------------------- */
+
+/// Commitment over pre-hashed key/value pairs: leaf `i` is
+/// `H::node(&k_i, &v_i)`, with no serialization.
+///
+/// This is its own type rather than a `StaticMerkleArray<(K, V), H>`
+/// because the generic methods there (`update_leaf`, `prove_item`,
+/// `verify_self_proofs`, ...) would hash the pair with `H::leaf`; only the
+/// methods that agree with this leaf rule are exposed. Check a proof with
+/// `verify_hashed_pair`.
+///
+/// A leaf is the same function as an internal node, so the two children of
+/// any internal node also "hash to" a node of the tree. Proofs bind
+/// `tree_len` and must have exactly the tree's depth, which rules out
+/// presenting an internal node as a leaf of the same tree, but a verifier
+/// that takes `tree_len` from the prover gets no such guarantee. Tag the
+/// key or value upstream if pairs and subtrees must never be confused.
+#[derive(Debug, Clone)]
+pub struct HashedPairArray<H: MerkleHasher> {
+    tree: StaticMerkleArray<(H::Digest, H::Digest), H>,
+}
+
+impl<H: MerkleHasher> HashedPairArray<H> {
+    /// Build a tree whose leaf `i` is `H::node(&pairs[i].0, &pairs[i].1)`.
+    ///
+    /// Returns `MerkleError::EmptyInput` if `pairs` is empty.
+    pub fn new_from_hashed_pairs(pairs: Vec<(H::Digest, H::Digest)>) -> Result<Self, MerkleError> {
+        if pairs.is_empty() {
+            return Err(MerkleError::EmptyInput);
+        }
+        let mut leaves: Vec<H::Digest> = Vec::new();
+        leaves.try_reserve_exact(pairs.len() + 1)?;
+        leaves.extend(pairs.iter().map(|(k, v)| H::node(k, v)));
+        Ok(Self {
+            tree: StaticMerkleArray::try_from_leaves(pairs, leaves)?,
+        })
+    }
+
+    /// The leaf committing to `key` and `value`.
+    pub fn leaf(key: &H::Digest, value: &H::Digest) -> H::Digest {
+        H::node(key, value)
+    }
+
+    /// Root commitment.
+    pub fn root(&self) -> H::Digest {
+        self.tree.root()
+    }
+
+    /// Number of pairs.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Is the array empty?
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// The committed pairs, in order.
+    pub fn pairs(&self) -> &[(H::Digest, H::Digest)] {
+        &self.tree.items
+    }
+
+    /// Consume the structure and return the committed pairs.
+    pub fn into_pairs(self) -> Vec<(H::Digest, H::Digest)> {
+        self.tree.into_items()
+    }
+
+    /// All positions holding the pair `(key, value)`.
+    pub fn positions_of(&self, key: &H::Digest, value: &H::Digest) -> &[usize] {
+        self.tree.positions_of_digest(&Self::leaf(key, value))
+    }
+
+    /// Build a proof of membership for a given index.
+    pub fn prove_index(&self, index: usize) -> Result<MerkleProof<H>, MerkleError> {
+        self.tree.prove_index(index)
+    }
+
+    /// Build a single proof of membership for several indices (see
+    /// `StaticMerkleArray::prove_indices`).
+    pub fn prove_indices(&self, indices: &[usize]) -> Result<MultiProof<H>, MerkleError> {
+        self.tree.prove_indices(indices)
+    }
+}
+
+/// Verify that `proof` commits to the pair `(key, value)` of a
+/// `HashedPairArray`.
+pub fn verify_hashed_pair<H: MerkleHasher>(
+    key: &H::Digest,
+    value: &H::Digest,
+    proof: &MerkleProof<H>,
+) -> bool {
+    proof.salt.is_none()
+        && !proof.position_bound
+        && proof.leaf == H::node(key, value)
+        && proof.verify()
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+
+    type Pairs = HashedPairArray<Sha512_256Hasher>;
+
+    #[test]
+    fn hashed_pairs_commit_to_node_of_key_and_value() {
+        let pairs: Vec<_> = (0..5u64)
+            .map(|i| {
+                (
+                    Sha512_256Hasher::leaf(&i),
+                    Sha512_256Hasher::leaf(&(i * 10 + 1)),
+                )
+            })
+            .collect();
+        let sm = Pairs::new_from_hashed_pairs(pairs.clone()).unwrap();
+        for (i, (k, v)) in pairs.iter().enumerate() {
+            let proof = sm.prove_index(i).unwrap();
+            assert_eq!(proof.leaf, Sha512_256Hasher::node(k, v));
+            assert!(verify_hashed_pair(k, v, &proof));
+            assert!(!verify_hashed_pair(v, k, &proof));
+            assert_eq!(sm.positions_of(k, v), &[i]);
+        }
+        assert_eq!(sm.pairs(), &pairs[..]);
+        assert_eq!(sm.into_pairs(), pairs);
+        assert!(matches!(
+            Pairs::new_from_hashed_pairs(vec![]),
+            Err(MerkleError::EmptyInput)
+        ));
+    }
+}
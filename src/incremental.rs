@@ -0,0 +1,136 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::MerkleHasher;
+
+/* -------------------------------------------------------------------------
+Incremental Root
+------------------------------------------------------------------------- */
+
+/// Running root over a stream of leaves, without keeping the tree.
+///
+/// Like `MmrAccumulator` it keeps one pending full subtree per set bit of
+/// the leaf count, so `push` is amortized O(1) hashes and memory is
+/// O(log n). Unlike MMR bagging, `current_root` pads odd levels by
+/// duplicating their last node, so it equals `StaticMerkleArray::root()`
+/// over the same leaves, `finalize_root` included. Each call costs
+/// O(log n) hashes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct IncrementalRoot<H: MerkleHasher> {
+    /// `peaks[h]` is the unpaired full subtree of height `h`, if any.
+    peaks: Vec<Option<H::Digest>>,
+    /// Number of pushed leaves.
+    leaf_count: usize,
+}
+
+impl<H: MerkleHasher> Default for IncrementalRoot<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: MerkleHasher> IncrementalRoot<H> {
+    /// No leaves yet.
+    pub fn new() -> Self {
+        Self {
+            peaks: Vec::new(),
+            leaf_count: 0,
+        }
+    }
+
+    /// State after `leaf_count` leaves, from the full subtrees of its
+    /// binary decomposition, largest first. `None` if the count of peaks is
+    /// not `leaf_count.count_ones()`.
+    pub(crate) fn from_peaks(leaf_count: usize, peaks: &[H::Digest]) -> Option<Self> {
+        if peaks.len() != leaf_count.count_ones() as usize {
+            return None;
+        }
+        let mut by_height = vec![None; (usize::BITS - leaf_count.leading_zeros()) as usize];
+        let heights = (0..by_height.len())
+            .rev()
+            .filter(|h| leaf_count >> h & 1 == 1);
+        for (h, peak) in heights.zip(peaks) {
+            by_height[h] = Some(*peak);
+        }
+        Some(Self {
+            peaks: by_height,
+            leaf_count,
+        })
+    }
+
+    /// Number of pushed leaves.
+    pub fn len(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Has nothing been pushed yet?
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count == 0
+    }
+
+    /// Append a leaf digest.
+    pub fn push(&mut self, leaf: H::Digest) {
+        let mut carry = leaf;
+        let mut h = 0;
+        while let Some(left) = self.peaks.get_mut(h).and_then(Option::take) {
+            carry = H::node(&left, &carry);
+            h += 1;
+        }
+        if self.peaks.len() == h {
+            self.peaks.push(None);
+        }
+        self.peaks[h] = Some(carry);
+        self.leaf_count += 1;
+    }
+
+    /// Hash `item` with `H::leaf` and append it.
+    pub fn push_item<T: Serialize>(&mut self, item: &T) {
+        self.push(H::leaf(item));
+    }
+
+    /// Root of `StaticMerkleArray` over the leaves so far, or `None` if empty.
+    pub fn current_root(&self) -> Option<H::Digest> {
+        let n = self.leaf_count;
+        if n == 0 {
+            return None;
+        }
+        let depth = crate::depth_for_len(n);
+        // `acc` is the partial (not full) last node at the current height.
+        let mut acc: Option<H::Digest> = None;
+        for h in 0..depth {
+            acc = match (self.peaks[h], acc) {
+                (Some(left), Some(right)) => Some(H::node(&left, &right)),
+                // A lone last node at an odd-width level is paired with itself.
+                (Some(last), None) | (None, Some(last)) => Some(H::node(&last, &last)),
+                (None, None) => None,
+            };
+        }
+        let top = acc.or_else(|| self.peaks.get(depth).copied().flatten())?;
+        Some(H::finalize_root(&top, n))
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::StaticMerkleArray;
+
+    #[test]
+    fn running_root_matches_static_array() {
+        let mut inc = IncrementalRoot::<Sha512_256Hasher>::new();
+        assert_eq!(inc.current_root(), None);
+        for n in 1u64..=40 {
+            inc.push_item(&(n - 1));
+            let sm: StaticMerkleArray<u64, Sha512_256Hasher> =
+                StaticMerkleArray::new((0..n).collect());
+            assert_eq!(inc.current_root(), Some(sm.root()), "n = {n}");
+        }
+        assert!(inc.peaks.len() <= 6);
+    }
+}
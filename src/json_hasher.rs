@@ -0,0 +1,129 @@
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::digest::Sha256Digest;
+use crate::{HashError, MerkleHasher};
+
+/* ------------------------- Canonical JSON encoding ------------------------- */
+
+/// Write `value` as canonical JSON.
+///
+/// Rules:
+/// - object members are sorted by key, comparing the keys' UTF-8 bytes;
+/// - no whitespace between tokens;
+/// - strings are escaped as `serde_json` does: `"` and `\` are escaped,
+///   control characters use `\b \f \n \r \t` or `\u00XX`, everything else
+///   (including non-ASCII) is emitted as raw UTF-8;
+/// - numbers use `serde_json`'s formatting and are *not* normalized between
+///   integer and float forms (`1` and `1.0` are different leaves);
+/// - array order is preserved.
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {
+            out.push_str(&value.to_string())
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (k, item) in items.iter().enumerate() {
+                if k > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            out.push('{');
+            for (k, (key, item)) in entries.into_iter().enumerate() {
+                if k > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/* ----------------------------- The Hasher --------------------------------- */
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+
+/// SHA-256 hasher whose leaves commit to canonical JSON.
+///
+/// A leaf is `H(0x00 || canonical_json(item))`, where `item` is first
+/// converted with `serde_json::to_value`; a node is `H(0x01 || left || right)`.
+/// Semantically equal JSON objects therefore get the same leaf regardless of
+/// key order, which `bincode` of a `serde_json::Value` does not guarantee.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CanonicalJsonSha256Hasher;
+
+impl MerkleHasher for CanonicalJsonSha256Hasher {
+    type Digest = Sha256Digest;
+
+    /// Panics if `item` can't be converted to JSON; `try_leaf` returns the
+    /// error instead.
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        Self::try_leaf(item).expect("json serialize")
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        let value = serde_json::to_value(item)?;
+        let mut h = Sha256::new();
+        h.update([LEAF_TAG]);
+        h.update(canonical_json(&value).as_bytes());
+        Ok(Sha256Digest(h.finalize().into()))
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        let mut h = Sha256::new();
+        h.update([NODE_TAG]);
+        h.update(left);
+        h.update(right);
+        Sha256Digest(h.finalize().into())
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn canonical_json_sorts_keys_and_strips_whitespace() {
+        let v: Value =
+            serde_json::from_str(r#"{ "b": [1, {"z": null, "a": "x\n"}], "a": true }"#).unwrap();
+        assert_eq!(
+            canonical_json(&v),
+            r#"{"a":true,"b":[1,{"a":"x\n","z":null}]}"#
+        );
+    }
+
+    #[test]
+    fn key_order_does_not_change_leaf() {
+        let a: Value = serde_json::from_str(r#"{"id": 1, "tags": ["x", "y"]}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"tags":["x","y"],"id":1}"#).unwrap();
+        assert_eq!(
+            CanonicalJsonSha256Hasher::leaf(&a),
+            CanonicalJsonSha256Hasher::leaf(&b)
+        );
+        assert_ne!(
+            CanonicalJsonSha256Hasher::leaf(&a),
+            CanonicalJsonSha256Hasher::leaf(&json!({"id": 2, "tags": ["x", "y"]}))
+        );
+    }
+}
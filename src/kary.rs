@@ -0,0 +1,212 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::marker::PhantomData;
+
+use crate::{MerkleError, MerkleHasher, Verify};
+
+/* -------------------------------------------------------------------------
+K-ary Merkle Proof
+------------------------------------------------------------------------- */
+
+/// One level of a k-ary authentication path.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(serialize = "D: Serialize", deserialize = "D: DeserializeOwned"))]
+pub struct KaryLevel<D> {
+    /// Position of the proven node among its `ARITY` siblings. Encoded as a
+    /// `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub position: usize,
+    /// The other `ARITY - 1` children, left to right.
+    pub siblings: Vec<D>,
+}
+
+/// A Merkle proof of inclusion in a `KaryMerkleArray`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct KaryProof<H: MerkleHasher> {
+    /// Original array index (0-based). Encoded as a `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub index: usize,
+    /// Number of children per internal node. Encoded as a `u64` on every
+    /// target.
+    #[serde(with = "crate::serde_u64")]
+    pub arity: usize,
+    /// Authentication path (bottom to top).
+    pub levels: Vec<KaryLevel<H::Digest>>,
+    /// The commitment root we expect.
+    pub root: H::Digest,
+    /// The leaf hash for the proven item.
+    pub leaf: H::Digest,
+}
+
+impl<H: MerkleHasher> KaryProof<H> {
+    pub fn verify(&self) -> bool {
+        let meter = crate::meter::Meter::start("verify_kary");
+        meter.finish(self.verify_unmetered())
+    }
+
+    fn verify_unmetered(&self) -> bool {
+        if self.arity < 2 {
+            return false;
+        }
+        let mut acc = self.leaf;
+        let mut i = self.index;
+        for level in &self.levels {
+            if level.position >= self.arity
+                || level.siblings.len() != self.arity - 1
+                || i % self.arity != level.position
+            {
+                return false;
+            }
+            let mut children = level.siblings.clone();
+            children.insert(level.position, acc);
+            acc = H::node_many(&children);
+            i /= self.arity;
+        }
+        i == 0 && acc == self.root
+    }
+}
+
+impl<H: MerkleHasher> Verify for KaryProof<H> {
+    fn verify(&self) -> bool {
+        KaryProof::verify(self)
+    }
+}
+
+/* -------------------------------------------------------------------------
+K-ary Static Merkle Array
+------------------------------------------------------------------------- */
+
+/// Static array commitment where every internal node has `ARITY` children.
+///
+/// Wider nodes give shallower trees: fewer levels per proof, at the cost of
+/// `ARITY - 1` siblings per level. Parents are `H::node_many(children)`, and a
+/// level whose length is not a multiple of `ARITY` is padded by repeating its
+/// last node. The root is the top node as is (no `finalize_root`), so with
+/// `ARITY = 2` it equals `StaticMerkleArray`'s for hashers with the default
+/// identity finalization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize, T: Serialize",
+    deserialize = "H::Digest: DeserializeOwned, T: DeserializeOwned"
+))]
+pub struct KaryMerkleArray<T, H, const ARITY: usize>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    items: Vec<T>,
+    /// Bottom-up levels; levels[0] = leaves, levels.last() = [root]
+    levels: Vec<Vec<H::Digest>>,
+    #[serde(skip)]
+    _hasher: PhantomData<H>,
+}
+
+impl<T, H, const ARITY: usize> KaryMerkleArray<T, H, ARITY>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    /// Build the structure from an array of items.
+    pub fn new(items: Vec<T>) -> Self {
+        assert!(ARITY >= 2, "arity must be at least 2");
+        assert!(!items.is_empty(), "array must be non-empty");
+
+        let mut levels = Vec::new();
+        let mut cur: Vec<H::Digest> = items.iter().map(H::leaf).collect();
+        while cur.len() > 1 {
+            let last = *cur.last().unwrap();
+            cur.resize(cur.len().next_multiple_of(ARITY), last);
+            let next = cur.chunks(ARITY).map(H::node_many).collect();
+            levels.push(cur);
+            cur = next;
+        }
+        levels.push(cur);
+
+        Self {
+            items,
+            levels,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Root commitment.
+    pub fn root(&self) -> H::Digest {
+        *self.levels.last().unwrap().first().unwrap()
+    }
+
+    /// Array length.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Is the array empty?
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Build a proof of membership for a given index.
+    pub fn prove_index(&self, index: usize) -> Result<KaryProof<H>, MerkleError> {
+        if index >= self.len() {
+            return Err(MerkleError::IndexOob);
+        }
+        let mut levels = Vec::with_capacity(self.levels.len() - 1);
+        let mut i = index;
+        for level_nodes in &self.levels[..self.levels.len() - 1] {
+            let position = i % ARITY;
+            let group = &level_nodes[i - position..i - position + ARITY];
+            let siblings = group
+                .iter()
+                .enumerate()
+                .filter(|(k, _)| *k != position)
+                .map(|(_, d)| *d)
+                .collect();
+            levels.push(KaryLevel { position, siblings });
+            i /= ARITY;
+        }
+
+        Ok(KaryProof {
+            index,
+            arity: ARITY,
+            levels,
+            root: self.root(),
+            leaf: self.levels[0][index],
+        })
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::StaticMerkleArray;
+
+    #[test]
+    fn binary_arity_matches_static_array() {
+        let arr: Vec<u64> = (0..13).collect();
+        let k: KaryMerkleArray<u64, Sha512_256Hasher, 2> = KaryMerkleArray::new(arr.clone());
+        let sm: StaticMerkleArray<u64, Sha512_256Hasher> = StaticMerkleArray::new(arr);
+        assert_eq!(k.root(), sm.root());
+    }
+
+    #[test]
+    fn quaternary_proofs_verify() {
+        let arr: Vec<u64> = (0..23).collect();
+        let k: KaryMerkleArray<u64, Sha512_256Hasher, 4> = KaryMerkleArray::new(arr.clone());
+        for (i, v) in arr.iter().enumerate() {
+            let proof = k.prove_index(i).unwrap();
+            assert_eq!(proof.levels.len(), 3);
+            assert!(proof.verify());
+            assert_eq!(proof.leaf, Sha512_256Hasher::leaf(v));
+        }
+
+        let mut bad = k.prove_index(5).unwrap();
+        bad.levels[0].position = 2;
+        assert!(!bad.verify());
+        assert!(matches!(k.prove_index(23), Err(MerkleError::IndexOob)));
+    }
+}
@@ -0,0 +1,378 @@
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
+use std::hash::Hash as StdHash;
+use std::marker::PhantomData;
+
+use crate::digest::Sha256Digest;
+use crate::{HashError, MerkleError, MerkleHasher, Side};
+
+/* -------------------------------------------------------------------------
+Stateful hasher trait
+------------------------------------------------------------------------- */
+
+/// A Merkle hasher that is a value rather than a type: its methods take
+/// `&self`, so it can carry a key or other parameters chosen at runtime.
+///
+/// `MerkleHasher` works through associated functions, which keeps proofs
+/// self-contained but leaves a keyed hasher nowhere to keep its key. Trees
+/// over a `StatefulMerkleHasher` are `KeyedMerkleArray`s, and their proofs
+/// are checked against a hasher instance (`KeyedProof::verify`). Any
+/// `MerkleHasher` can be used here through `Unkeyed`.
+pub trait StatefulMerkleHasher {
+    type Digest: Copy + Clone + Eq + StdHash + Serialize + DeserializeOwned + Debug;
+
+    /// Hash a leaf value.
+    fn leaf<T: Serialize>(&self, item: &T) -> Self::Digest;
+
+    /// Hash an internal node from its left/right child digests.
+    fn node(&self, left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+
+    /// Fallible `leaf`. Defaults to `Ok(self.leaf(item))`.
+    fn try_leaf<T: Serialize>(&self, item: &T) -> Result<Self::Digest, HashError> {
+        Ok(self.leaf(item))
+    }
+
+    /// Map the top node to the published root (see
+    /// `MerkleHasher::finalize_root`). Defaults to the identity.
+    fn finalize_root(&self, top: &Self::Digest, _n_leaves: usize) -> Self::Digest {
+        *top
+    }
+}
+
+/// Adapter using a stateless `MerkleHasher` as a `StatefulMerkleHasher`.
+/// A `KeyedMerkleArray<T, Unkeyed<H>>` has the same root as a
+/// `StaticMerkleArray<T, H>` over the same items.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unkeyed<H>(PhantomData<H>);
+
+impl<H: MerkleHasher> StatefulMerkleHasher for Unkeyed<H> {
+    type Digest = H::Digest;
+
+    fn leaf<T: Serialize>(&self, item: &T) -> Self::Digest {
+        H::leaf(item)
+    }
+
+    fn node(&self, left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        H::node(left, right)
+    }
+
+    fn try_leaf<T: Serialize>(&self, item: &T) -> Result<Self::Digest, HashError> {
+        H::try_leaf(item)
+    }
+
+    fn finalize_root(&self, top: &Self::Digest, n_leaves: usize) -> Self::Digest {
+        H::finalize_root(top, n_leaves)
+    }
+}
+
+/* -------------------------------------------------------------------------
+HMAC-SHA-256 hasher
+------------------------------------------------------------------------- */
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+const BLOCK: usize = 64;
+
+/// Keyed SHA-256 hasher: a leaf is `HMAC(key, 0x00 || bincode(item))`, a
+/// node is `HMAC(key, 0x01 || left || right)`, with HMAC as in RFC 2104.
+///
+/// Without the key, leaves can't be recomputed from guessed values and
+/// proofs can't be checked, so the root only means something to key holders.
+#[derive(Clone)]
+pub struct HmacSha256Hasher {
+    /// `key ^ ipad` and `key ^ opad`, with the key padded to one block.
+    inner: [u8; BLOCK],
+    outer: [u8; BLOCK],
+}
+
+impl HmacSha256Hasher {
+    /// A hasher keyed with `key`; keys longer than a SHA-256 block are
+    /// hashed first, as HMAC specifies.
+    pub fn new(key: &[u8]) -> Self {
+        let mut block = [0u8; BLOCK];
+        if key.len() > BLOCK {
+            block[..32].copy_from_slice(&Sha256::digest(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        Self {
+            inner: block.map(|b| b ^ 0x36),
+            outer: block.map(|b| b ^ 0x5c),
+        }
+    }
+
+    /// HMAC of the concatenation of `parts`.
+    fn mac(&self, parts: &[&[u8]]) -> Sha256Digest {
+        let mut h = Sha256::new();
+        h.update(self.inner);
+        for part in parts {
+            h.update(part);
+        }
+        let inner = h.finalize();
+        Sha256Digest(
+            Sha256::new()
+                .chain_update(self.outer)
+                .chain_update(inner)
+                .finalize()
+                .into(),
+        )
+    }
+}
+
+// Keep the key out of logs.
+impl Debug for HmacSha256Hasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HmacSha256Hasher(..)")
+    }
+}
+
+impl StatefulMerkleHasher for HmacSha256Hasher {
+    type Digest = Sha256Digest;
+
+    /// Panics if `item` fails to serialize; `try_leaf` returns the error.
+    fn leaf<T: Serialize>(&self, item: &T) -> Self::Digest {
+        self.try_leaf(item).expect("bincode serialize")
+    }
+
+    fn try_leaf<T: Serialize>(&self, item: &T) -> Result<Self::Digest, HashError> {
+        Ok(self.mac(&[&[LEAF_TAG], &bincode::serialize(item)?]))
+    }
+
+    fn node(&self, left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        self.mac(&[&[NODE_TAG], left.as_ref(), right.as_ref()])
+    }
+}
+
+/* -------------------------------------------------------------------------
+Keyed Merkle Proof
+------------------------------------------------------------------------- */
+
+/// A Merkle proof of inclusion in a `KeyedMerkleArray`. Unlike
+/// `MerkleProof`, checking it needs the hasher the tree was built with.
+#[derive(Debug, Clone, Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct KeyedProof<H: StatefulMerkleHasher> {
+    /// Original array index (0-based). Encoded as a `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub index: usize,
+    /// Number of leaves in the committed array (bound into `root`). Encoded
+    /// as a `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub tree_len: usize,
+    /// Sibling hashes with their sides (bottom to top).
+    pub siblings: Vec<(H::Digest, Side)>,
+    /// The commitment root we expect.
+    pub root: H::Digest,
+    /// The leaf hash for the proven item.
+    pub leaf: H::Digest,
+}
+
+impl<H: StatefulMerkleHasher> KeyedProof<H> {
+    /// Fold the siblings with `hasher`, finalize and compare with `root`.
+    ///
+    /// Rejects, before hashing, proofs whose shape doesn't fit `tree_len` or
+    /// whose sides disagree with the bits of `index` (as
+    /// `MerkleProof::is_valid_structure`).
+    pub fn verify(&self, hasher: &H) -> bool {
+        if !crate::path_matches(self.index, self.tree_len, &self.siblings) {
+            return false;
+        }
+        let acc = self
+            .siblings
+            .iter()
+            .fold(self.leaf, |acc, (sib, side)| match side {
+                Side::Left => hasher.node(sib, &acc),
+                Side::Right => hasher.node(&acc, sib),
+            });
+        hasher.finalize_root(&acc, self.tree_len) == self.root
+    }
+
+    /// Whether `value` hashes to the proof's leaf under `hasher` and the
+    /// proof verifies.
+    pub fn verify_value<T: Serialize>(&self, hasher: &H, value: &T) -> bool {
+        hasher.leaf(value) == self.leaf && self.verify(hasher)
+    }
+}
+
+/* -------------------------------------------------------------------------
+Keyed Static Merkle Array
+------------------------------------------------------------------------- */
+
+/// Static array commitment over a hasher instance, for keyed or otherwise
+/// parameterized hashing.
+///
+/// The tree has the same shape as `StaticMerkleArray` (odd levels padded by
+/// duplicating their last node, root through `finalize_root`). The hasher is
+/// kept in the tree for proving but is not serialized with anything, so a key
+/// never ends up in a proof.
+#[derive(Debug, Clone)]
+pub struct KeyedMerkleArray<T, H: StatefulMerkleHasher> {
+    items: Vec<T>,
+    /// Bottom-up levels; levels[0] = leaves, levels.last() = [top node]
+    levels: Vec<Vec<H::Digest>>,
+    root: H::Digest,
+    hasher: H,
+}
+
+impl<T, H> KeyedMerkleArray<T, H>
+where
+    T: Serialize,
+    H: StatefulMerkleHasher,
+{
+    /// Build the structure from an array of items, hashing with `hasher`.
+    pub fn new_with_hasher(items: Vec<T>, hasher: H) -> Self {
+        assert!(!items.is_empty(), "array must be non-empty");
+        Self::try_new_with_hasher(items, hasher).expect("failed to build tree")
+    }
+
+    /// Fallible `new_with_hasher`: `MerkleError::EmptyInput` for no items,
+    /// `MerkleError::Hasher` if a leaf fails to hash.
+    pub fn try_new_with_hasher(items: Vec<T>, hasher: H) -> Result<Self, MerkleError> {
+        if items.is_empty() {
+            return Err(MerkleError::EmptyInput);
+        }
+        let mut cur = items
+            .iter()
+            .map(|item| hasher.try_leaf(item))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(MerkleError::Hasher)?;
+
+        let mut levels = Vec::new();
+        while cur.len() > 1 {
+            if cur.len() % 2 == 1 {
+                cur.push(*cur.last().unwrap());
+            }
+            let next = cur.chunks(2).map(|p| hasher.node(&p[0], &p[1])).collect();
+            levels.push(cur);
+            cur = next;
+        }
+        let root = hasher.finalize_root(&cur[0], items.len());
+        levels.push(cur);
+
+        Ok(Self {
+            items,
+            levels,
+            root,
+            hasher,
+        })
+    }
+
+    /// Root commitment.
+    pub fn root(&self) -> H::Digest {
+        self.root
+    }
+
+    /// The hasher the tree was built with.
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Array length.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Is the array empty?
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Item at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    /// Build a proof of membership for a given index.
+    pub fn prove_index(&self, index: usize) -> Result<KeyedProof<H>, MerkleError> {
+        if index >= self.len() {
+            return Err(MerkleError::IndexOob);
+        }
+        let upper = &self.levels[..self.levels.len() - 1];
+        let siblings = upper
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| {
+                let i = index >> level;
+                let side = if i % 2 == 1 { Side::Left } else { Side::Right };
+                (nodes[i ^ 1], side)
+            })
+            .collect();
+        Ok(KeyedProof {
+            index,
+            tree_len: self.len(),
+            siblings,
+            root: self.root,
+            leaf: self.levels[0][index],
+        })
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::StaticMerkleArray;
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        // Test case 2 (short key).
+        let mac = HmacSha256Hasher::new(b"Jefe").mac(&[b"what do ya want", b" for nothing?"]);
+        assert_eq!(
+            hex::encode(mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Test case 6 (key longer than a block).
+        let mac = HmacSha256Hasher::new(&[0xaa; 131])
+            .mac(&[b"Test Using Larger Than Block-Size Key - Hash Key First"]);
+        assert_eq!(
+            hex::encode(mac),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn unkeyed_matches_static_array() {
+        let items: Vec<u64> = (0..13).collect();
+        let keyed = KeyedMerkleArray::new_with_hasher(
+            items.clone(),
+            Unkeyed::<Sha512_256Hasher>::default(),
+        );
+        let sm = StaticMerkleArray::<u64, Sha512_256Hasher>::new(items);
+        assert_eq!(keyed.root(), sm.root());
+        assert_eq!(
+            keyed.prove_index(12).unwrap().siblings,
+            sm.prove_index(12).unwrap().siblings
+        );
+    }
+
+    #[test]
+    fn keyed_proofs_need_the_key() {
+        let items: Vec<u64> = (0..11).collect();
+        let a = KeyedMerkleArray::new_with_hasher(items.clone(), HmacSha256Hasher::new(b"key a"));
+        let b = KeyedMerkleArray::new_with_hasher(items, HmacSha256Hasher::new(b"key b"));
+        assert_ne!(a.root(), b.root());
+
+        for i in 0..a.len() {
+            let proof = a.prove_index(i).unwrap();
+            assert!(proof.verify_value(a.hasher(), &(i as u64)));
+            assert!(!proof.verify_value(b.hasher(), &(i as u64)));
+        }
+        let proof = a.prove_index(3).unwrap();
+        assert!(!proof.verify_value(a.hasher(), &4u64));
+        // The sides spell out index 3; the proof can't be moved to another slot.
+        let mut moved = proof.clone();
+        moved.index = 2;
+        assert!(!moved.verify(a.hasher()));
+        assert!(matches!(a.prove_index(11), Err(MerkleError::IndexOob)));
+        assert!(matches!(
+            KeyedMerkleArray::<u64, _>::try_new_with_hasher(vec![], HmacSha256Hasher::new(b"k")),
+            Err(MerkleError::EmptyInput)
+        ));
+    }
+}
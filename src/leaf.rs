@@ -0,0 +1,325 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+pub use static_merkle_array_derive::MerkleLeaf;
+
+/* -------------------------------------------------------------------------
+Canonical leaf encoding
+------------------------------------------------------------------------- */
+
+/// Stable, field-by-field byte encoding for leaf values.
+///
+/// Unlike `bincode`, the encoding is fixed by this crate and does not depend
+/// on the serde layout of the type:
+/// - integers are fixed-width big-endian, `bool` is one byte (`0`/`1`);
+/// - strings, vectors and slices are a `u64` big-endian length, then the elements;
+/// - `Option` is a `0`/`1` tag byte, then the value if present;
+/// - arrays and tuples are their elements in order, without a length;
+/// - structs (via `#[derive(MerkleLeaf)]`) are their fields in declaration order;
+/// - enums are a `u32` big-endian variant index, then the variant's fields.
+pub trait MerkleLeaf: Sized {
+    /// Append the canonical encoding of `self` to `out`.
+    fn encode_leaf(&self, out: &mut Vec<u8>);
+
+    /// Decode a value from the front of `input`, advancing it.
+    fn decode_leaf(input: &mut &[u8]) -> Option<Self>;
+
+    /// The canonical encoding as a fresh buffer.
+    fn leaf_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_leaf(&mut out);
+        out
+    }
+}
+
+/// Split `n` bytes off the front of `input`.
+#[doc(hidden)]
+pub fn take<'a>(input: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if input.len() < n {
+        return None;
+    }
+    let (head, tail) = input.split_at(n);
+    *input = tail;
+    Some(head)
+}
+
+macro_rules! int_leaf {
+    ($($t:ty),*) => {$(
+        impl MerkleLeaf for $t {
+            fn encode_leaf(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes());
+            }
+
+            fn decode_leaf(input: &mut &[u8]) -> Option<Self> {
+                let bytes = take(input, std::mem::size_of::<$t>())?;
+                Some(<$t>::from_be_bytes(bytes.try_into().ok()?))
+            }
+        }
+    )*};
+}
+
+int_leaf!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl MerkleLeaf for bool {
+    fn encode_leaf(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn decode_leaf(input: &mut &[u8]) -> Option<Self> {
+        match take(input, 1)?[0] {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl MerkleLeaf for String {
+    fn encode_leaf(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode_leaf(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode_leaf(input: &mut &[u8]) -> Option<Self> {
+        let len = usize::try_from(u64::decode_leaf(input)?).ok()?;
+        String::from_utf8(take(input, len)?.to_vec()).ok()
+    }
+}
+
+impl<T: MerkleLeaf> MerkleLeaf for Vec<T> {
+    fn encode_leaf(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode_leaf(out);
+        for x in self {
+            x.encode_leaf(out);
+        }
+    }
+
+    fn decode_leaf(input: &mut &[u8]) -> Option<Self> {
+        let len = usize::try_from(u64::decode_leaf(input)?).ok()?;
+        // Every element takes at least one byte; don't trust huge lengths.
+        let mut v = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            v.push(T::decode_leaf(input)?);
+        }
+        Some(v)
+    }
+}
+
+impl<T: MerkleLeaf> MerkleLeaf for Option<T> {
+    fn encode_leaf(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(x) => {
+                out.push(1);
+                x.encode_leaf(out);
+            }
+        }
+    }
+
+    fn decode_leaf(input: &mut &[u8]) -> Option<Self> {
+        match take(input, 1)?[0] {
+            0 => Some(None),
+            1 => Some(Some(T::decode_leaf(input)?)),
+            _ => None,
+        }
+    }
+}
+
+impl<T: MerkleLeaf, const N: usize> MerkleLeaf for [T; N] {
+    fn encode_leaf(&self, out: &mut Vec<u8>) {
+        for x in self {
+            x.encode_leaf(out);
+        }
+    }
+
+    fn decode_leaf(input: &mut &[u8]) -> Option<Self> {
+        let mut v = Vec::with_capacity(N);
+        for _ in 0..N {
+            v.push(T::decode_leaf(input)?);
+        }
+        v.try_into().ok()
+    }
+}
+
+macro_rules! tuple_leaf {
+    ($($name:ident),+) => {
+        impl<$($name: MerkleLeaf),+> MerkleLeaf for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode_leaf(&self, out: &mut Vec<u8>) {
+                let ($($name,)+) = self;
+                $($name.encode_leaf(out);)+
+            }
+
+            fn decode_leaf(input: &mut &[u8]) -> Option<Self> {
+                Some(($($name::decode_leaf(input)?,)+))
+            }
+        }
+    };
+}
+
+tuple_leaf!(A);
+tuple_leaf!(A, B);
+tuple_leaf!(A, B, C);
+tuple_leaf!(A, B, C, D);
+
+/* ------------------------------ Canonical<T> ------------------------------ */
+
+/// Wrapper that (de)serializes `T` as its canonical `MerkleLeaf` bytes.
+///
+/// Hashers see a serde byte string, so `StaticMerkleArray<Canonical<T>, H>`
+/// commits to the canonical encoding with any `MerkleHasher`, and saved trees
+/// keep that encoding on disk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Canonical<T>(pub T);
+
+impl<T: MerkleLeaf> Serialize for Canonical<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0.leaf_bytes())
+    }
+}
+
+impl<'de, T: MerkleLeaf> Deserialize<'de> for Canonical<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes: Vec<u8> = serde_bytes_vec(deserializer)?;
+        let mut input = bytes.as_slice();
+        match T::decode_leaf(&mut input) {
+            Some(x) if input.is_empty() => Ok(Canonical(x)),
+            _ => Err(de::Error::custom("invalid canonical leaf encoding")),
+        }
+    }
+}
+
+/// Deserialize a serde byte string into an owned buffer.
+fn serde_bytes_vec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    struct BytesVisitor;
+
+    impl<'de> de::Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("a byte string")
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut v = Vec::new();
+            while let Some(b) = seq.next_element()? {
+                v.push(b);
+            }
+            Ok(v)
+        }
+    }
+
+    deserializer.deserialize_byte_buf(BytesVisitor)
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::{verify_value_with_proof, StaticMerkleArray};
+
+    #[derive(Clone, Debug, PartialEq, Eq, MerkleLeaf)]
+    struct Account {
+        id: u32,
+        name: String,
+        flags: (bool, Option<u8>),
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, MerkleLeaf)]
+    enum Event {
+        Open,
+        Transfer(u16, u16),
+        Close { reason: String },
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, MerkleLeaf)]
+    struct Transfer {
+        input: u64,
+        out: u64,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, MerkleLeaf)]
+    enum Io {
+        Pair { input: u8, out: u8 },
+    }
+
+    #[test]
+    fn fields_named_like_the_parameters() {
+        let t = Transfer { input: 1, out: 2 };
+        assert_eq!(
+            t.leaf_bytes(),
+            vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2]
+        );
+        assert_eq!(
+            Transfer::decode_leaf(&mut t.leaf_bytes().as_slice()),
+            Some(t)
+        );
+
+        let io = Io::Pair { input: 3, out: 4 };
+        assert_eq!(io.leaf_bytes(), vec![0, 0, 0, 0, 3, 4]);
+        assert_eq!(Io::decode_leaf(&mut io.leaf_bytes().as_slice()), Some(io));
+    }
+
+    #[test]
+    fn derived_encoding_is_field_by_field() {
+        let a = Account {
+            id: 7,
+            name: "ab".into(),
+            flags: (true, Some(3)),
+        };
+        assert_eq!(
+            a.leaf_bytes(),
+            vec![0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 2, b'a', b'b', 1, 1, 3]
+        );
+        assert_eq!(
+            Account::decode_leaf(&mut a.leaf_bytes().as_slice()),
+            Some(a)
+        );
+
+        assert_eq!(Event::Open.leaf_bytes(), vec![0, 0, 0, 0]);
+        assert_eq!(
+            Event::Transfer(1, 2).leaf_bytes(),
+            vec![0, 0, 0, 1, 0, 1, 0, 2]
+        );
+        for e in [
+            Event::Open,
+            Event::Transfer(5, 6),
+            Event::Close { reason: "x".into() },
+        ] {
+            assert_eq!(Event::decode_leaf(&mut e.leaf_bytes().as_slice()), Some(e));
+        }
+    }
+
+    #[test]
+    fn canonical_items_commit_and_persist() {
+        let items: Vec<Canonical<Account>> = (0..5)
+            .map(|i| {
+                Canonical(Account {
+                    id: i,
+                    name: format!("user{i}"),
+                    flags: (i % 2 == 0, None),
+                })
+            })
+            .collect();
+        let sm: StaticMerkleArray<_, Sha512_256Hasher> = StaticMerkleArray::new(items.clone());
+        let proof = sm.prove_index(3).unwrap();
+        assert!(verify_value_with_proof(&items[3], &proof));
+
+        let path = std::env::temp_dir().join(format!("sma_canonical_{}.bin", std::process::id()));
+        sm.save_to_file(&path).unwrap();
+        let loaded: StaticMerkleArray<Canonical<Account>, Sha512_256Hasher> =
+            StaticMerkleArray::load_from_file(&path).unwrap();
+        assert_eq!(loaded.root(), sm.root());
+        assert_eq!(loaded.positions_of(&items[4]), vec![4]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
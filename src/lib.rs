@@ -4,13 +4,60 @@ use std::fmt::Debug;
 use std::fs;
 use std::hash::Hash as StdHash;
 use std::io::{Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+// Lets the derive macros' `::static_merkle_array::...` paths resolve inside this crate.
+extern crate self as static_merkle_array;
+
+#[cfg(feature = "tokio")]
+pub mod async_io;
+pub mod bundle;
+pub mod bytes_hasher;
+pub mod commitment;
+pub mod consistency;
+pub mod digest;
+pub mod disk;
+pub mod fn_hasher;
+#[cfg(feature = "mimc")]
 mod hash_constants;
+pub mod hashed_pairs;
+pub mod incremental;
+pub mod json_hasher;
+pub mod kary;
+pub mod keyed;
+pub mod leaf;
+pub mod map;
+mod meter;
+#[cfg(feature = "mimc")]
 mod mimc;
+#[cfg(feature = "mimc")]
 pub mod mimc_bn254_hasher;
+pub mod mmr;
+#[cfg(feature = "mimc")]
+pub mod pedersen;
+#[cfg(feature = "mimc")]
+pub mod poseidon;
+#[cfg(feature = "mimc")]
+pub mod poseidon2;
+pub mod proof_set;
+#[cfg(feature = "interop-rs-merkle")]
+pub mod rs_merkle_interop;
+mod serde_u64;
+pub mod sha3_hasher;
+pub mod sha_hasher;
+pub mod shard;
+pub mod sorted;
+pub mod test_vectors;
+pub mod truncated;
+#[macro_use]
+mod trace;
+#[cfg(feature = "mimc")]
 mod utils;
 /* --------------------------- MerkleHasher trait --------------------------- */
 
+/// Error reported by a fallible hash backend (see `MerkleHasher::try_leaf`).
+pub type HashError = Box<dyn std::error::Error + Send + Sync>;
+
 /// Pluggable hash behavior for the Merkle tree.
 ///
 /// `Digest` is the node/leaf hash type (e.g., `[u8; 32]`, a newtype, etc).
@@ -18,17 +65,213 @@ mod utils;
 pub trait MerkleHasher {
     type Digest: Copy + Clone + Eq + StdHash + Serialize + DeserializeOwned + Debug;
 
+    /// Whether `node(a, b) == node(b, a)` (sorted-pair hashing), so proofs
+    /// don't need to record sibling sides.
+    const SORTED: bool = false;
+
+    /// Size of a digest in bytes: its `bincode` encoding and, for byte-like
+    /// digests, `as_ref().len()`. Used for proof size math and packed
+    /// formats. Defaults to 32, as for every bundled hasher.
+    const DIGEST_SIZE: usize = 32;
+
     /// Hash a leaf value.
     fn leaf<T: Serialize>(item: &T) -> Self::Digest;
 
     /// Hash an internal node from its left/right child digests.
     fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+
+    /// Fallible `leaf`, for backends that can fail (HSMs, remote signers).
+    /// `StaticMerkleArray::try_new` hashes leaves through this.
+    ///
+    /// Defaults to `Ok(Self::leaf(item))`.
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        Ok(Self::leaf(item))
+    }
+
+    /// Hash a leaf value together with its position, for position-bound
+    /// trees (see `StaticMerkleArray::new_position_bound`).
+    ///
+    /// Defaults to `Self::leaf(&(index as u64, item))`: the index is encoded
+    /// ahead of the item, so equal values at different positions get
+    /// different leaves.
+    fn leaf_with_index<T: Serialize>(item: &T, index: usize) -> Self::Digest {
+        Self::leaf(&(index as u64, item))
+    }
+
+    /// Fallible `leaf_with_index`. Defaults to
+    /// `Self::try_leaf(&(index as u64, item))`, matching `leaf_with_index`.
+    fn try_leaf_with_index<T: Serialize>(
+        item: &T,
+        index: usize,
+    ) -> Result<Self::Digest, HashError> {
+        Self::try_leaf(&(index as u64, item))
+    }
+
+    /// Fallible `node`. Defaults to `Ok(Self::node(left, right))`.
+    fn try_node(left: &Self::Digest, right: &Self::Digest) -> Result<Self::Digest, HashError> {
+        Ok(Self::node(left, right))
+    }
+
+    /// Hash an internal node into a caller-provided digest.
+    ///
+    /// Defaults to `*out = Self::node(left, right)`. Hashers with large or
+    /// heap-backed digests can override it to write in place; since tree
+    /// building goes through `try_node_into`, override that one too.
+    fn node_into(out: &mut Self::Digest, left: &Self::Digest, right: &Self::Digest) {
+        *out = Self::node(left, right);
+    }
+
+    /// Fallible `node_into`; tree building uses it to fill each level's
+    /// preallocated buffer.
+    ///
+    /// Defaults to `*out = Self::try_node(left, right)?`.
+    fn try_node_into(
+        out: &mut Self::Digest,
+        left: &Self::Digest,
+        right: &Self::Digest,
+    ) -> Result<(), HashError> {
+        *out = Self::try_node(left, right)?;
+        Ok(())
+    }
+
+    /// Hash an internal node with any number of children (for k-ary trees).
+    ///
+    /// Defaults to folding `node` left to right, so two children give
+    /// `node(left, right)`. Panics if `children` is empty.
+    fn node_many(children: &[Self::Digest]) -> Self::Digest {
+        let (first, rest) = children.split_first().expect("at least one child");
+        rest.iter().fold(*first, |acc, c| Self::node(&acc, c))
+    }
+
+    /// Turn the top of the tree into the published root, binding in the
+    /// number of leaves.
+    ///
+    /// Without this step the root of a tree is just another internal node, so
+    /// a subtree root (or the root of a smaller tree built with the same hash)
+    /// can be passed off as the root of a whole commitment. Hashers shared
+    /// between protocols should override this, e.g. with a tagged hash of
+    /// `top || n_leaves`, so full roots are never confused with internal
+    /// nodes. Defaults to the identity.
+    fn finalize_root(top: &Self::Digest, _n_leaves: usize) -> Self::Digest {
+        *top
+    }
+
+    /// Digest for an intentionally empty slot (see
+    /// `StaticMerkleArray::new_fixed_width`).
+    ///
+    /// Defaults to hashing the leaf domain with no data, `Self::leaf(&())`,
+    /// which no non-empty encoding can produce. Hashers whose `leaf` ignores
+    /// the encoding length should override it.
+    fn empty_leaf() -> Self::Digest {
+        Self::leaf(&())
+    }
+
+    /// Canonical "zero" digest used for empty subtrees (all-zero by default).
+    fn zero_digest() -> Self::Digest
+    where
+        Self::Digest: Default,
+    {
+        Self::Digest::default()
+    }
 }
 
 /* -------------------------------------------------------------------------
 Merkle Proof
 ------------------------------------------------------------------------- */
 
+/// Common verification interface for the crate's proof types.
+pub trait Verify {
+    /// Recompute and check the proof against the root it carries.
+    fn verify(&self) -> bool;
+}
+
+impl<H: MerkleHasher> Verify for MerkleProof<H> {
+    fn verify(&self) -> bool {
+        MerkleProof::verify(self)
+    }
+}
+
+impl<H: MerkleHasher> Verify for MultiProof<H> {
+    fn verify(&self) -> bool {
+        MultiProof::verify(self)
+    }
+}
+
+/// `bincode` encodes `usize` values and sequence lengths as `u64`.
+const BINCODE_LEN: usize = 8;
+/// `bincode` encodes a unit enum variant as its `u32` index.
+const BINCODE_SIDE: usize = 4;
+/// `bincode` encodes a `bool` as one byte.
+const BINCODE_BOOL: usize = 1;
+
+/// `bincode` length of an optional salt: a one-byte tag, then the bytes.
+fn bincode_salt_len(salt: &Option<Salt>) -> usize {
+    1 + salt.map_or(0, |s| s.len())
+}
+
+/// Number of sibling levels in a tree over `len` leaves (`ceil(log2(len))`).
+fn depth_for_len(len: usize) -> usize {
+    len.next_power_of_two().trailing_zeros() as usize
+}
+
+/// Whether a proof for `index` with `depth` siblings can come from a tree of
+/// `tree_len` leaves: the index is in range and the depth is exactly the
+/// tree's.
+fn shape_matches(index: usize, tree_len: usize, depth: usize) -> bool {
+    index < tree_len && depth == depth_for_len(tree_len)
+}
+
+/// `shape_matches`, plus each sibling's side agreeing with the
+/// corresponding bit of `index` (bit set means the sibling is on the left).
+fn path_matches<D>(index: usize, tree_len: usize, siblings: &[(D, Side)]) -> bool {
+    shape_matches(index, tree_len, siblings.len())
+        && siblings
+            .iter()
+            .enumerate()
+            .all(|(level, (_, side))| (*side == Side::Left) == index_bit(index, level))
+}
+
+/// Hash `start` up through `siblings` (bottom to top) to the top digest.
+fn fold_path<H: MerkleHasher>(start: &H::Digest, siblings: &[(H::Digest, Side)]) -> H::Digest {
+    siblings.iter().fold(*start, |acc, (sib, side)| match side {
+        Side::Left => H::node(sib, &acc),
+        Side::Right => H::node(&acc, sib),
+    })
+}
+
+/// Per-tree random salt mixed into every leaf (see `StaticMerkleArray::new_salted`).
+pub type Salt = [u8; 32];
+
+/// Leaf digest of `item`: `H::leaf(item)`, with `(salt, item)` in place of
+/// `item` for a salted tree, and through `H::leaf_with_index` when
+/// `position` is given (position-bound trees).
+fn leaf_digest<H: MerkleHasher, T: Serialize>(
+    salt: &Option<Salt>,
+    position: Option<usize>,
+    item: &T,
+) -> H::Digest {
+    match (salt, position) {
+        (Some(salt), Some(i)) => H::leaf_with_index(&(salt, item), i),
+        (Some(salt), None) => H::leaf(&(salt, item)),
+        (None, Some(i)) => H::leaf_with_index(item, i),
+        (None, None) => H::leaf(item),
+    }
+}
+
+/// Fallible `leaf_digest`, through `H::try_leaf` / `H::try_leaf_with_index`.
+fn try_leaf_digest<H: MerkleHasher, T: Serialize>(
+    salt: &Option<Salt>,
+    position: Option<usize>,
+    item: &T,
+) -> Result<H::Digest, HashError> {
+    match (salt, position) {
+        (Some(salt), Some(i)) => H::try_leaf_with_index(&(salt, item), i),
+        (Some(salt), None) => H::try_leaf(&(salt, item)),
+        (None, Some(i)) => H::try_leaf_with_index(item, i),
+        (None, None) => H::try_leaf(item),
+    }
+}
+
 /// Indicates whether a sibling hash was to the left or right of the node
 /// we are proving.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -38,47 +281,199 @@ pub enum Side {
 }
 
 /// A Merkle proof of inclusion for a single array element.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(
     serialize = "H::Digest: Serialize",
     deserialize = "H::Digest: DeserializeOwned"
 ))]
 pub struct MerkleProof<H: MerkleHasher> {
-    /// Original array index (0-based).
+    /// Original array index (0-based). Encoded as a `u64` on every target.
+    #[serde(with = "serde_u64")]
     pub index: usize,
+    /// Number of leaves in the committed array (bound into `root`). Encoded
+    /// as a `u64` on every target.
+    #[serde(with = "serde_u64")]
+    pub tree_len: usize,
     /// Sibling hashes + which side they came from (bottom to top).
     pub siblings: Vec<(H::Digest, Side)>, // bottom -> top
     /// The commitment root we expect.
     pub root: H::Digest,
     /// The leaf hash for the proven item.
     pub leaf: H::Digest,
+    /// Salt of the tree the proof came from, needed to recompute `leaf` from
+    /// the item (`None` for unsalted trees).
+    pub salt: Option<Salt>,
+    /// Whether the tree's leaves hash in their index (see
+    /// `StaticMerkleArray::new_position_bound`).
+    pub position_bound: bool,
 }
 
 impl<H: MerkleHasher> MerkleProof<H> {
+    /// The leaf digest `value` would have at `index` in the proof's tree,
+    /// salt and position binding included.
+    pub fn leaf_for<T: Serialize>(&self, value: &T) -> H::Digest {
+        leaf_digest::<H, T>(&self.salt, self.position_bound.then_some(self.index), value)
+    }
+
+    /// Recompute the root from `leaf` and `siblings`, finalize it with
+    /// `H::finalize_root(_, tree_len)` and compare with `root`.
+    ///
+    /// Also rejects proofs whose shape doesn't match `tree_len`: the index
+    /// must be below it and the sibling count must equal the depth of a tree
+    /// with that many leaves, so a deep proof can't pass for one from a
+    /// smaller tree (or the other way round). These checks
+    /// (`is_valid_structure`) run first, before any hashing.
     pub fn verify(&self) -> bool {
-        let mut acc = self.leaf;
-        for (sib, side) in &self.siblings {
-            acc = match side {
-                Side::Left => H::node(sib, &acc),
-                Side::Right => H::node(&acc, sib),
-            };
+        let _span = trace_span!("verify", index = self.index, tree_len = self.tree_len);
+        let meter = meter::Meter::start("verify");
+        meter.finish(self.verify_unmetered())
+    }
+
+    /// `verify` without recording metrics, for the crate's own checks.
+    pub(crate) fn verify_unmetered(&self) -> bool {
+        self.is_valid_structure() && self.verify_fast()
+    }
+
+    /// Cheap well-formedness check, without hashing: the index and sibling
+    /// count fit `tree_len` (as in `verify`), and each sibling's side matches
+    /// the corresponding bit of `index` (bit set means the sibling is on the
+    /// left).
+    ///
+    /// Lets a server drop garbage proofs before paying for the hash
+    /// recomputation, which matters for expensive hashers such as MiMC.
+    pub fn is_valid_structure(&self) -> bool {
+        path_matches(self.index, self.tree_len, &self.siblings)
+    }
+
+    /// Like `verify`, but without the shape checks: only recompute, finalize
+    /// and compare with `root`.
+    ///
+    /// For hot loops over proofs whose structure the caller has already
+    /// validated. It assumes, without checking, that
+    /// - `index < tree_len`, and
+    /// - `siblings.len() == ceil(log2(tree_len))` (zero for a one-leaf tree).
+    ///
+    /// If either fails, a proof can pass here that `verify` rejects, e.g. a
+    /// path from a deeper tree presented under a smaller `tree_len` whose
+    /// finalized root happens to be accepted. Prefer `verify` otherwise.
+    pub fn verify_fast(&self) -> bool {
+        H::finalize_root(&self.recompute_root(), self.tree_len) == self.root
+    }
+
+    /// Fold `leaf` through `siblings` and return the resulting top digest,
+    /// before `finalize_root` and without comparing it to `root` (useful for
+    /// diagnosing mismatches).
+    pub fn recompute_root(&self) -> H::Digest {
+        fold_path::<H>(&self.leaf, &self.siblings)
+    }
+
+    /// Whether two proofs authenticate the same thing: same `index`,
+    /// `tree_len`, `leaf` and `root`, and sibling paths that recompute to the same top digest.
+    ///
+    /// Unlike `==`, the raw `siblings` may differ (e.g. proofs produced by
+    /// different code paths), as long as they fold to the same result.
+    pub fn is_equivalent(&self, other: &Self) -> bool {
+        self.index == other.index
+            && self.tree_len == other.tree_len
+            && self.leaf == other.leaf
+            && self.root == other.root
+            && self.recompute_root() == other.recompute_root()
+    }
+
+    /// Levels (0 = leaves) at which this proof's sibling is the proven node's
+    /// own padding duplicate.
+    ///
+    /// Padding rule: whenever a level has an odd number of nodes, its last
+    /// node is duplicated and paired with itself. So at level `l` the path
+    /// node `index >> l` is padded iff it is the last of an odd number of
+    /// nodes, `ceil(tree_len / 2^l)`. This depends only on `index` and
+    /// `tree_len` (which `root` binds), so it needs no extra proof data.
+    pub fn padded_levels(&self) -> Vec<usize> {
+        (0..depth_for_len(self.tree_len))
+            .filter(|&l| {
+                let width = level_width(self.tree_len, l);
+                width % 2 == 1 && self.index >> l == width - 1
+            })
+            .collect()
+    }
+
+    /// Whether the path touches a duplicated node (see `padded_levels`), for
+    /// external verifiers that pad differently and need to reconcile.
+    pub fn is_padded_edge(&self) -> bool {
+        !self.padded_levels().is_empty()
+    }
+
+    /// The path directions: bit `l` of `index` for each of the
+    /// `siblings.len()` levels, least significant first. `true` means the
+    /// path node is a right child (its sibling is `Side::Left`), as Merkle
+    /// path gadgets in circuits expect.
+    pub fn index_bits(&self) -> Vec<bool> {
+        (0..self.siblings.len())
+            .map(|l| index_bit(self.index, l))
+            .collect()
+    }
+
+    /// Like `verify`, but also require at least `min_depth` siblings.
+    ///
+    /// A proof with no siblings only claims `leaf == root`, i.e. a one-element
+    /// commitment. Protocols that know their trees are larger should use this
+    /// to reject such degenerate single-leaf proofs.
+    pub fn verify_min_depth(&self, min_depth: usize) -> bool {
+        self.siblings.len() >= min_depth && self.verify()
+    }
+
+    /// Chain this proof with `upper`, a proof that this tree's root is a leaf
+    /// of a larger (forest) tree, into one proof against the forest root.
+    ///
+    /// The combined `index` is `upper.index << siblings.len() | index`, and
+    /// the combined `tree_len` is `(upper.tree_len - 1) << siblings.len()`
+    /// plus this tree's `tree_len`: this tree's leaves counted after
+    /// `upper.tree_len - 1` full subtrees, so the shape checks in `verify`
+    /// hold. That count is only the forest's real leaf count when every lower
+    /// tree except the last is full (`2^siblings.len()` leaves). The chain
+    /// only recomputes the forest root for hashers with the default (identity)
+    /// `finalize_root`.
+    ///
+    /// Fails with `MerkleError::RootMismatch` unless `self.root == upper.leaf`.
+    pub fn extend_with(&self, upper: &MerkleProof<H>) -> Result<MerkleProof<H>, MerkleError> {
+        if self.root != upper.leaf {
+            return Err(MerkleError::RootMismatch);
         }
-        acc == self.root
+        let depth = self.siblings.len();
+        let shift = |x: usize| {
+            u32::try_from(depth)
+                .ok()
+                .and_then(|d| x.checked_shl(d))
+                .filter(|&y| y >> depth == x)
+                .ok_or(MerkleError::DepthMismatch {
+                    index: x,
+                    depth: depth + upper.siblings.len(),
+                })
+        };
+        let tree_len = shift(upper.tree_len.saturating_sub(1))?
+            .checked_add(self.tree_len)
+            .ok_or(MerkleError::IndexOob)?;
+
+        let mut siblings = self.siblings.clone();
+        siblings.extend_from_slice(&upper.siblings);
+        Ok(MerkleProof {
+            index: shift(upper.index)? | self.index,
+            tree_len,
+            siblings,
+            root: upper.root,
+            leaf: self.leaf,
+            salt: self.salt,
+            position_bound: self.position_bound,
+        })
     }
 
     pub fn save_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), MerkleError> {
-        let bytes = bincode::serialize(self)?;
-        std::fs::write(path, bytes)?;
-        Ok(())
+        write_versioned(path, self)
     }
 
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, MerkleError> {
-        let mut file = fs::File::open(path)?;
-        let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes)?;
-        let me: Self = bincode::deserialize(&bytes)?;
-        Ok(me)
-    } 
+        read_versioned(path)
+    }
 
     pub fn get_merkle_root(&self) -> H::Digest {
         self.root
@@ -87,6 +482,391 @@ impl<H: MerkleHasher> MerkleProof<H> {
     pub fn get_leaf(&self) -> H::Digest {
         self.leaf
     }
+
+    /// Length in bytes of the `bincode` encoding of this proof, computed from
+    /// the sibling count and digest size without serializing the whole proof.
+    pub fn serialized_len(&self) -> usize {
+        let d = H::DIGEST_SIZE;
+        // index + tree_len + siblings (length prefix, then digest + side tag each) + root + leaf + salt
+        // + position_bound
+        BINCODE_LEN
+            + BINCODE_LEN
+            + BINCODE_LEN
+            + self.siblings.len() * (d + BINCODE_SIDE)
+            + 2 * d
+            + bincode_salt_len(&self.salt)
+            + BINCODE_BOOL
+    }
+}
+
+// `PartialEq`/`Eq`/`Hash` are written out rather than derived so they don't
+// require `H` itself (usually a unit struct) to implement them.
+impl<H: MerkleHasher> PartialEq for MerkleProof<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+            && self.tree_len == other.tree_len
+            && self.siblings == other.siblings
+            && self.root == other.root
+            && self.leaf == other.leaf
+            && self.salt == other.salt
+            && self.position_bound == other.position_bound
+    }
+}
+
+impl<H: MerkleHasher> Eq for MerkleProof<H> {}
+
+/// Hashes `index`, `leaf` and `root` only. Equal proofs agree on those, so
+/// this is consistent with `Eq`; proofs differing only in their siblings or
+/// `tree_len` simply land in the same bucket.
+impl<H: MerkleHasher> StdHash for MerkleProof<H> {
+    fn hash<S: std::hash::Hasher>(&self, state: &mut S) {
+        self.index.hash(state);
+        self.leaf.hash(state);
+        self.root.hash(state);
+    }
+}
+
+/// Incrementally assembles a `MerkleProof`, validating each sibling as it
+/// arrives (e.g. when decoding a proof from wire parts).
+#[derive(Debug, Clone)]
+pub struct MerkleProofBuilder<H: MerkleHasher> {
+    proof: MerkleProof<H>,
+}
+
+impl<H: MerkleHasher> MerkleProofBuilder<H> {
+    pub fn new(index: usize, tree_len: usize, leaf: H::Digest, root: H::Digest) -> Self {
+        Self {
+            proof: MerkleProof {
+                index,
+                tree_len,
+                siblings: Vec::new(),
+                root,
+                leaf,
+                salt: None,
+                position_bound: false,
+            },
+        }
+    }
+
+    /// Record the salt of a salted tree (see `MerkleProof::salt`).
+    pub fn salt(&mut self, salt: Salt) -> &mut Self {
+        self.proof.salt = Some(salt);
+        self
+    }
+
+    /// Mark the proof as coming from a position-bound tree.
+    pub fn position_bound(&mut self) -> &mut Self {
+        self.proof.position_bound = true;
+        self
+    }
+
+    /// Append the next sibling (bottom to top).
+    ///
+    /// Fails with `MerkleError::SideMismatch` if `side` disagrees with the
+    /// corresponding bit of the index (bit set means the sibling is on the left).
+    pub fn push_sibling(
+        &mut self,
+        digest: H::Digest,
+        side: Side,
+    ) -> Result<&mut Self, MerkleError> {
+        let position = self.proof.siblings.len();
+        let is_right = index_bit(self.proof.index, position);
+        if (side == Side::Left) != is_right {
+            return Err(MerkleError::SideMismatch { position });
+        }
+        self.proof.siblings.push((digest, side));
+        Ok(self)
+    }
+
+    /// Finish, checking that the index and sibling count fit `tree_len`.
+    pub fn build(self) -> Result<MerkleProof<H>, MerkleError> {
+        let p = &self.proof;
+        if !shape_matches(p.index, p.tree_len, p.siblings.len()) {
+            return Err(MerkleError::DepthMismatch {
+                index: self.proof.index,
+                depth: self.proof.siblings.len(),
+            });
+        }
+        Ok(self.proof)
+    }
+}
+
+/// Bit `level` of `index` (false beyond the width of `usize`).
+fn index_bit(index: usize, level: usize) -> bool {
+    u32::try_from(level)
+        .ok()
+        .and_then(|l| index.checked_shr(l))
+        .is_some_and(|rest| rest & 1 == 1)
+}
+
+impl<H: MerkleHasher> MerkleProof<H>
+where
+    H::Digest: AsRef<[u8]>,
+{
+    /// Copy the proof into a hasher-agnostic byte form.
+    pub fn into_byte_proof(&self) -> ByteProof {
+        ByteProof {
+            index: self.index,
+            tree_len: self.tree_len,
+            siblings: self
+                .siblings
+                .iter()
+                .map(|(d, side)| (d.as_ref().to_vec(), *side))
+                .collect(),
+            root: self.root.as_ref().to_vec(),
+            leaf: self.leaf.as_ref().to_vec(),
+        }
+    }
+
+    /// The path in the order fixed-depth hardware verifiers ingest it: root
+    /// toward leaf, padded to `depth` entries, with the index read MSB-first.
+    ///
+    /// - Entry `k` of the vector is the sibling at level `depth - 1 - k`, so
+    ///   the first entry is the child of the root and the last is the leaf's
+    ///   sibling, each as its 32 digest bytes.
+    /// - When the proof has fewer than `depth` siblings, the first
+    ///   `depth - siblings.len()` entries are all-zero padding. They stand
+    ///   for levels the tree doesn't have, and the verifier must skip them;
+    ///   for a tree that really has `depth` levels, build it with
+    ///   `pad_to_depth` instead.
+    /// - The `u64` is `index`. Step `k` reads bit `depth - 1 - k`: 1 means
+    ///   the path goes right, so that step's sibling is on the left. Padding
+    ///   steps read 0.
+    ///
+    /// `None` if the proof has more than `depth` siblings, `depth > 64`, or
+    /// digests aren't 32 bytes.
+    pub fn to_hardware_format(&self, depth: usize) -> Option<HardwareProof> {
+        if depth > 64 || self.siblings.len() > depth {
+            return None;
+        }
+        let mut path = vec![[0u8; 32]; depth - self.siblings.len()];
+        for (d, _) in self.siblings.iter().rev() {
+            path.push(d.as_ref().try_into().ok()?);
+        }
+        Some((path, self.index as u64))
+    }
+}
+
+/// Root-to-leaf sibling bytes and the MSB-first index, from
+/// `MerkleProof::to_hardware_format`.
+pub type HardwareProof = (Vec<[u8; 32]>, u64);
+
+/// A Merkle proof with digests as plain bytes, decoupled from the hasher type.
+///
+/// Useful for transport and for verifiers that pick the hash function at runtime.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ByteProof {
+    /// Original array index (0-based). Encoded as a `u64` on every target.
+    #[serde(with = "serde_u64")]
+    pub index: usize,
+    /// Number of leaves in the committed array. Encoded as a `u64` on every
+    /// target.
+    #[serde(with = "serde_u64")]
+    pub tree_len: usize,
+    /// Sibling hashes + which side they came from (bottom to top).
+    pub siblings: Vec<(Vec<u8>, Side)>,
+    /// The commitment root we expect.
+    pub root: Vec<u8>,
+    /// The leaf hash for the proven item.
+    pub leaf: Vec<u8>,
+}
+
+impl ByteProof {
+    /// Verify using `node(left, right)` to combine two child digests.
+    ///
+    /// The folded top is compared with `root` as is, so this only fits
+    /// hashers that keep the default (identity) `finalize_root`.
+    pub fn verify_with<F>(&self, node: F) -> bool
+    where
+        F: Fn(&[u8], &[u8]) -> Vec<u8>,
+    {
+        let mut acc = self.leaf.clone();
+        for (sib, side) in &self.siblings {
+            acc = match side {
+                Side::Left => node(sib, &acc),
+                Side::Right => node(&acc, sib),
+            };
+        }
+        acc == self.root
+    }
+}
+
+/* -------------------------------------------------------------------------
+Subtree Proof
+------------------------------------------------------------------------- */
+
+/// Number of real (unpadded) nodes at `level` of a tree over `len` leaves,
+/// i.e. `ceil(len / 2^level)`; zero for an empty tree or a level past the top.
+fn level_width(len: usize, level: usize) -> usize {
+    if len == 0 || level > depth_for_len(len) {
+        return 0;
+    }
+    ((len - 1) >> level) + 1
+}
+
+/// A proof that an internal node (the root of an aligned subtree) belongs to
+/// the commitment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct SubtreeProof<H: MerkleHasher> {
+    /// Height of the node (0 = leaf). Encoded as a `u64` on every target,
+    /// like the other two positions.
+    #[serde(with = "serde_u64")]
+    pub level: usize,
+    /// Position of the node within its level (0-based).
+    #[serde(with = "serde_u64")]
+    pub node_index: usize,
+    /// Number of leaves in the committed array (bound into `root`).
+    #[serde(with = "serde_u64")]
+    pub tree_len: usize,
+    /// Sibling hashes + which side they came from (from `level` up).
+    pub siblings: Vec<(H::Digest, Side)>,
+    /// The commitment root we expect.
+    pub root: H::Digest,
+    /// The proven internal node.
+    pub node: H::Digest,
+}
+
+impl<H: MerkleHasher> SubtreeProof<H> {
+    /// Check the node position against `tree_len`, then fold `node` through
+    /// `siblings`, finalize and compare with `root`.
+    pub fn verify(&self) -> bool {
+        let meter = meter::Meter::start("verify_subtree");
+        meter.finish(self.verify_unmetered())
+    }
+
+    fn verify_unmetered(&self) -> bool {
+        if self.node_index >= level_width(self.tree_len, self.level)
+            || self.level + self.siblings.len() != depth_for_len(self.tree_len)
+        {
+            return false;
+        }
+        H::finalize_root(&fold_path::<H>(&self.node, &self.siblings), self.tree_len) == self.root
+    }
+}
+
+impl<H: MerkleHasher> Verify for SubtreeProof<H> {
+    fn verify(&self) -> bool {
+        SubtreeProof::verify(self)
+    }
+}
+
+/* -------------------------------------------------------------------------
+Multi Proof
+------------------------------------------------------------------------- */
+
+/// A compact proof of inclusion for several array elements at once.
+///
+/// Siblings shared between the individual paths (or derivable from other
+/// proven leaves) are stored only once. `indices` is kept sorted and
+/// deduplicated, with `leaves[k]` the leaf hash at `indices[k]`, so the proof
+/// authenticates a *set* of (index, leaf) pairs regardless of the order in
+/// which the indices were requested.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct MultiProof<H: MerkleHasher> {
+    /// Proven array indices, sorted ascending and without duplicates.
+    /// Encoded as `u64`s on every target.
+    #[serde(with = "serde_u64::vec")]
+    pub indices: Vec<usize>,
+    /// Leaf hashes, aligned with `indices`.
+    pub leaves: Vec<H::Digest>,
+    /// Sibling hashes not derivable from the proven leaves, in the order the
+    /// verifier consumes them (bottom to top, left to right within a level).
+    pub siblings: Vec<H::Digest>,
+    /// Number of levels between the leaves and the root.
+    #[serde(with = "serde_u64")]
+    pub depth: usize,
+    /// Number of leaves in the committed array (bound into `root`).
+    #[serde(with = "serde_u64")]
+    pub tree_len: usize,
+    /// The commitment root we expect.
+    pub root: H::Digest,
+    /// Salt of the tree the proof came from (see `MerkleProof::salt`).
+    pub salt: Option<Salt>,
+    /// See `MerkleProof::position_bound`.
+    pub position_bound: bool,
+}
+
+impl<H: MerkleHasher> MultiProof<H> {
+    /// Length in bytes of the `bincode` encoding of this proof, computed from
+    /// the element counts and digest size without serializing the whole proof.
+    pub fn serialized_len(&self) -> usize {
+        let d = H::DIGEST_SIZE;
+        (BINCODE_LEN + self.indices.len() * BINCODE_LEN)
+            + (BINCODE_LEN + self.leaves.len() * d)
+            + (BINCODE_LEN + self.siblings.len() * d)
+            + BINCODE_LEN
+            + BINCODE_LEN
+            + d
+            + bincode_salt_len(&self.salt)
+            + BINCODE_BOOL
+    }
+
+    pub fn verify(&self) -> bool {
+        let meter = meter::Meter::start("verify_multi");
+        meter.finish(self.verify_unmetered())
+    }
+
+    fn verify_unmetered(&self) -> bool {
+        if self.indices.is_empty() || self.indices.len() != self.leaves.len() {
+            return false;
+        }
+        let last = self.indices[self.indices.len() - 1];
+        if !shape_matches(last, self.tree_len, self.depth) {
+            return false;
+        }
+        if self.indices.windows(2).any(|w| w[0] >= w[1]) {
+            return false;
+        }
+
+        let mut known: Vec<(usize, H::Digest)> = self
+            .indices
+            .iter()
+            .copied()
+            .zip(self.leaves.iter().copied())
+            .collect();
+        let mut sibs = self.siblings.iter();
+
+        for _ in 0..self.depth {
+            let mut next = Vec::with_capacity(known.len());
+            let mut k = 0;
+            while k < known.len() {
+                let (i, digest) = known[k];
+                let (left, right) = if i.is_multiple_of(2) {
+                    // Right sibling is either the next known node or comes from the proof.
+                    if k + 1 < known.len() && known[k + 1].0 == i + 1 {
+                        k += 1;
+                        (digest, known[k].1)
+                    } else {
+                        match sibs.next() {
+                            Some(s) => (digest, *s),
+                            None => return false,
+                        }
+                    }
+                } else {
+                    match sibs.next() {
+                        Some(s) => (*s, digest),
+                        None => return false,
+                    }
+                };
+                next.push((i / 2, H::node(&left, &right)));
+                k += 1;
+            }
+            known = next;
+        }
+
+        sibs.next().is_none()
+            && known.len() == 1
+            && known[0].0 == 0
+            && H::finalize_root(&known[0].1, self.tree_len) == self.root
+    }
 }
 
 /* -------------------------------------------------------------------------
@@ -99,10 +879,126 @@ pub enum MerkleError {
     IndexOob,
     #[error("item not found in array")]
     NotFound,
-    #[error("io: {0}")]
-    Io(#[from] std::io::Error),
+    #[error("occurrence {requested} requested but item occurs {found} time(s)")]
+    OccurrenceOob { found: usize, requested: usize },
+    #[error("array must be non-empty")]
+    EmptyInput,
+    #[error("array has {len} items, more than the limit of {max}")]
+    TooLarge { len: usize, max: usize },
+    #[error("sibling {position} is on the wrong side for the proof index")]
+    SideMismatch { position: usize },
+    #[error("index {index} cannot exist in a tree of depth {depth}")]
+    DepthMismatch { index: usize, depth: usize },
+    #[error("proof bytes length {len} is not a multiple of the digest size {digest_len}")]
+    BadProofLength { len: usize, digest_len: usize },
+    #[error("tree of {tree_len} leaves has odd levels, where rs_merkle promotes nodes this crate duplicates")]
+    PromotedNodes { tree_len: usize },
+    #[error("proof root does not match the upper proof's leaf")]
+    RootMismatch,
+    #[error("hash backend failed: {0}")]
+    Hasher(#[source] HashError),
+    #[error("allocation failed: {0}")]
+    Alloc(#[from] std::collections::TryReserveError),
+    #[error("io: {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
     #[error("bincode: {0}")]
     Codec(#[from] Box<bincode::ErrorKind>),
+    #[error("file format version {found}, expected {expected}")]
+    VersionMismatch { found: u8, expected: u8 },
+    #[error("hasher is degenerate: {check}")]
+    DegenerateHasher { check: &'static str },
+}
+
+/// Why a proof failed to verify (see `verify_value_with_proof_explain`).
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError<D: Debug> {
+    #[error("proof shape does not fit: index {index}, {depth} siblings, tree of {tree_len}")]
+    Shape {
+        index: usize,
+        tree_len: usize,
+        depth: usize,
+    },
+    #[error("value hashes to {computed:?} but the proof's leaf is {expected:?}")]
+    LeafMismatch { computed: D, expected: D },
+    #[error("path recomputes root {computed:?} but the proof's root is {expected:?}")]
+    RootMismatch { computed: D, expected: D },
+}
+
+/// Why `authenticate` rejected an `(item, index, proof)` triple.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum AuthError<D: Debug> {
+    #[error("proof is for index {found}, expected {expected}")]
+    IndexMismatch { expected: usize, found: usize },
+    #[error("proof commits to root {found:?}, expected {expected:?}")]
+    UntrustedRoot { expected: D, found: D },
+    #[error(transparent)]
+    Proof(#[from] VerifyError<D>),
+}
+
+/* -------------------------------------------------------------------------
+File format
+------------------------------------------------------------------------- */
+
+/// First bytes of every file written by a `save_to_file`.
+pub const FILE_MAGIC: [u8; 4] = *b"SMA\x00";
+
+/// Current file format version, written right after `FILE_MAGIC`. The rest
+/// of the file is the `bincode` encoding of the saved value. Bump it
+/// whenever a saved type's encoding changes.
+///
+/// Files without the magic predate versioning and are reported as version 0.
+/// Version 2 added the leaf salt to trees and proofs, version 3 the
+/// position-binding flag, version 4 the padding and empty item of trees
+/// (`pad_to_depth`, `new_fixed_width`).
+pub const FORMAT_VERSION: u8 = 4;
+
+/// `map_err` adapter turning an io error into `MerkleError::Io` for `path`.
+/// The path is only copied if there is an error.
+fn io_err<P: AsRef<Path>>(path: P) -> impl FnOnce(std::io::Error) -> MerkleError {
+    move |source| MerkleError::Io {
+        path: path.as_ref().to_path_buf(),
+        source,
+    }
+}
+
+/// File contents for `value`: the magic, the version, then `bincode`.
+fn encode_versioned<S: Serialize>(value: &S) -> Result<Vec<u8>, MerkleError> {
+    let mut bytes = FILE_MAGIC.to_vec();
+    bytes.push(FORMAT_VERSION);
+    bincode::serialize_into(&mut bytes, value)?;
+    Ok(bytes)
+}
+
+/// Inverse of `encode_versioned`, checking the header first.
+fn decode_versioned<D: DeserializeOwned>(bytes: &[u8]) -> Result<D, MerkleError> {
+    let found = match bytes.strip_prefix(&FILE_MAGIC[..]) {
+        Some([version, ..]) => *version,
+        _ => 0,
+    };
+    if found != FORMAT_VERSION {
+        return Err(MerkleError::VersionMismatch {
+            found,
+            expected: FORMAT_VERSION,
+        });
+    }
+    Ok(bincode::deserialize(&bytes[FILE_MAGIC.len() + 1..])?)
+}
+
+fn write_versioned<P: AsRef<Path>, S: Serialize>(path: P, value: &S) -> Result<(), MerkleError> {
+    let bytes = encode_versioned(value)?;
+    fs::write(&path, bytes).map_err(io_err(&path))?;
+    Ok(())
+}
+
+fn read_versioned<P: AsRef<Path>, D: DeserializeOwned>(path: P) -> Result<D, MerkleError> {
+    let mut file = fs::File::open(&path).map_err(io_err(&path))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(io_err(&path))?;
+    decode_versioned(&bytes)
 }
 
 /* -------------------------------------------------------------------------
@@ -128,6 +1024,20 @@ where
     levels: Vec<Vec<H::Digest>>,
     /// Map leaf-digest -> positions (handles duplicates)
     index_map: HashMap<H::Digest, Vec<usize>>,
+    /// Salt mixed into every leaf, if built with `new_salted`.
+    salt: Option<Salt>,
+    /// Leaves hash in their index, if built with `new_position_bound`.
+    position_bound: bool,
+    /// `H::empty_leaf()` slots after the items' leaves, if padded with
+    /// `pad_to_depth`.
+    padding: usize,
+    /// Item whose slots commit to `H::empty_leaf()` rather than a leaf of
+    /// their own: `Some(None)` for `new_fixed_width` trees.
+    empty_item: Option<T>,
+    /// Per-leaf sibling offsets, if enabled with `with_proof_index`; built on
+    /// first use. Not saved, since it only depends on the length.
+    #[serde(skip)]
+    sibling_offsets: Option<OnceLock<Box<[u32]>>>,
 }
 
 impl<T, H> StaticMerkleArray<T, H>
@@ -143,40 +1053,241 @@ where
     /// - If a level has odd length, duplicate the last node (standard padding).
     pub fn new(items: Vec<T>) -> Self {
         assert!(!items.is_empty(), "array must be non-empty");
+        Self::try_new(items).expect("failed to build tree")
+    }
 
-        let leaves: Vec<H::Digest> = items.iter().map(H::leaf).collect();
+    /// Like `new`, but reports failures instead of panicking or aborting.
+    ///
+    /// Every allocation goes through `try_reserve`, so running out of memory
+    /// yields `MerkleError::Alloc`; an empty input yields `MerkleError::EmptyInput`.
+    /// Hashing goes through `H::try_leaf` / `H::try_node_into`, and a backend
+    /// failure is returned as `MerkleError::Hasher`.
+    pub fn try_new(items: Vec<T>) -> Result<Self, MerkleError> {
+        Self::try_build(items, None, false, None)
+    }
 
-        // Build levels with duplicate padding
-        let mut levels = Vec::new();
-        let mut cur = leaves.clone();
-        while cur.len() > 1 {
-            if cur.len() % 2 == 1 {
-                cur.push(*cur.last().unwrap());
-            }
-            let mut next = Vec::with_capacity((cur.len() + 1) / 2);
-            for i in (0..cur.len()).step_by(2) {
-                next.push(H::node(&cur[i], &cur[i + 1]));
-            }
-            levels.push(cur);
-            cur = next;
-        }
-        levels.push(cur);
+    /// Like `try_new`, but run `sanity_check_hasher` first.
+    pub fn try_new_checked(items: Vec<T>) -> Result<Self, MerkleError> {
+        Self::sanity_check_hasher()?;
+        Self::try_new(items)
+    }
 
-        let mut idx: HashMap<H::Digest, Vec<usize>> = HashMap::new();
-        for (i, leaf) in leaves.iter().enumerate() {
-            idx.entry(*leaf).or_default().push(i);
+    /// Smoke-test `H` on dummy inputs, to catch a misconfigured hasher (say,
+    /// one returning a constant) before it produces a root that
+    /// authenticates anything.
+    ///
+    /// Checks that two distinct leaves differ, that a parent differs from
+    /// both children, and, unless `H::SORTED`, that swapping the children
+    /// changes the parent. Fails with `MerkleError::DegenerateHasher` naming
+    /// the first failed check, or `MerkleError::Hasher` if hashing fails.
+    pub fn sanity_check_hasher() -> Result<(), MerkleError> {
+        let degenerate = |check| Err(MerkleError::DegenerateHasher { check });
+        let a = H::try_leaf(&"static_merkle_array/sanity/a").map_err(MerkleError::Hasher)?;
+        let b = H::try_leaf(&"static_merkle_array/sanity/b").map_err(MerkleError::Hasher)?;
+        if a == b {
+            return degenerate("distinct leaves hash equal");
         }
-
-        Self {
+        let ab = H::try_node(&a, &b).map_err(MerkleError::Hasher)?;
+        if ab == a || ab == b {
+            return degenerate("node equals one of its children");
+        }
+        if !H::SORTED && H::try_node(&b, &a).map_err(MerkleError::Hasher)? == ab {
+            return degenerate("node ignores child order");
+        }
+        Ok(())
+    }
+
+    /// Build with every leaf salted: leaf `i` is `H::leaf(&(salt, items[i]))`.
+    ///
+    /// A fresh random salt per tree stops anyone holding only the leaves
+    /// (from proofs, say) from matching them against all possible values of
+    /// a small domain such as flags or small integers. The salt is stored in
+    /// the structure, so `positions_of` / `prove_item` still work, and every
+    /// proof carries it so `verify_value_with_proof` can recompute the leaf.
+    /// Keep it secret from anyone who shouldn't be able to do that matching.
+    pub fn new_salted(items: Vec<T>, salt: Salt) -> Self {
+        assert!(!items.is_empty(), "array must be non-empty");
+        Self::try_build(items, Some(salt), false, None).expect("failed to build tree")
+    }
+
+    /// Build with every leaf bound to its position: leaf `i` is
+    /// `H::leaf_with_index(&items[i], i)`.
+    ///
+    /// Equal values at different positions get different leaves, so a
+    /// `prove_index` proof binds its position cryptographically rather than
+    /// only through `index`: rewriting the index breaks the leaf. The flag is
+    /// stored in the tree and its proofs, and `verify_value_with_proof`
+    /// hashes the value with the proof's index. `positions_of` and
+    /// `prove_item` still work, but compare items instead of leaf digests, so
+    /// they scan the array.
+    pub fn new_position_bound(items: Vec<T>) -> Self {
+        assert!(!items.is_empty(), "array must be non-empty");
+        Self::try_build(items, None, true, None).expect("failed to build tree")
+    }
+
+    fn try_build(
+        items: Vec<T>,
+        salt: Option<Salt>,
+        position_bound: bool,
+        empty_item: Option<T>,
+    ) -> Result<Self, MerkleError> {
+        if items.is_empty() {
+            return Err(MerkleError::EmptyInput);
+        }
+        let _span = trace_span!("build", leaves = items.len());
+
+        let mut leaves: Vec<H::Digest> = Vec::new();
+        // One spare slot for the padding duplicate.
+        leaves.try_reserve_exact(items.len() + 1)?;
+        for (i, item) in items.iter().enumerate() {
+            let leaf = if empty_item.as_ref() == Some(item) {
+                H::empty_leaf()
+            } else {
+                let position = position_bound.then_some(i);
+                try_leaf_digest::<H, T>(&salt, position, item).map_err(MerkleError::Hasher)?
+            };
+            leaves.push(leaf);
+        }
+        let mut tree = Self::try_from_leaves(items, leaves)?;
+        tree.salt = salt;
+        tree.position_bound = position_bound;
+        tree.empty_item = empty_item;
+        Ok(tree)
+    }
+
+    /// Index `leaves` and build the levels above them. `leaves[i]` must be
+    /// the leaf digest for `items[i]`; any leaves past the items are padding.
+    fn try_from_leaves(items: Vec<T>, leaves: Vec<H::Digest>) -> Result<Self, MerkleError> {
+        let padding = leaves.len() - items.len();
+        // Index the leaves while we still only borrow them...
+        let mut idx: HashMap<H::Digest, Vec<usize>> = HashMap::new();
+        idx.try_reserve(items.len())?;
+        for (i, leaf) in leaves[..items.len()].iter().enumerate() {
+            let positions = idx.entry(*leaf).or_default();
+            positions.try_reserve(1)?;
+            positions.push(i);
+        }
+
+        // ...then move them into the bottom level: each level is owned by
+        // `cur` until it is pushed into `levels`, so no level is ever copied.
+        let mut levels = Vec::new();
+        levels.try_reserve_exact(usize::BITS as usize + 1)?;
+        let mut cur = leaves;
+        while cur.len() > 1 {
+            if cur.len() % 2 == 1 {
+                cur.try_reserve(1)?;
+                cur.push(*cur.last().unwrap());
+            }
+            let mut next = Vec::new();
+            // Spare slot again, so the next level can be padded in place.
+            next.try_reserve_exact(cur.len() / 2 + 1)?;
+            next.resize(cur.len() / 2, cur[0]);
+            for (out, pair) in next.iter_mut().zip(cur.chunks_exact(2)) {
+                H::try_node_into(out, &pair[0], &pair[1]).map_err(MerkleError::Hasher)?;
+            }
+            levels.push(cur);
+            cur = next;
+        }
+        levels.push(cur);
+        trace_event!(depth = levels.len() - 1, "levels built");
+
+        Ok(Self {
             items,
             levels,
             index_map: idx,
+            salt: None,
+            position_bound: false,
+            padding,
+            empty_item: None,
+            sibling_offsets: None,
+        })
+    }
+
+    /// Like `try_new`, but refuse arrays longer than `max_len` with
+    /// `MerkleError::TooLarge` before hashing anything.
+    pub fn new_with_limit(items: Vec<T>, max_len: usize) -> Result<Self, MerkleError> {
+        if items.len() > max_len {
+            return Err(MerkleError::TooLarge {
+                len: items.len(),
+                max: max_len,
+            });
+        }
+        Self::try_new(items)
+    }
+
+    /// Build one tree per column of a table, hashing the leaves row by row,
+    /// plus a combined root: the root of a `StaticMerkleArray<H::Digest, H>`
+    /// over the column roots, in column order.
+    ///
+    /// Panics if there are no columns, the columns are empty, or their
+    /// lengths differ.
+    pub fn new_columns(columns: Vec<Vec<T>>) -> (Vec<Self>, H::Digest) {
+        let rows = columns.first().expect("need at least one column").len();
+        assert!(rows > 0, "array must be non-empty");
+        assert!(
+            columns.iter().all(|c| c.len() == rows),
+            "columns must have equal length"
+        );
+
+        let mut leaves: Vec<Vec<H::Digest>> =
+            columns.iter().map(|_| Vec::with_capacity(rows)).collect();
+        for r in 0..rows {
+            for (col, out) in columns.iter().zip(&mut leaves) {
+                out.push(H::leaf(&col[r]));
+            }
         }
+        let trees: Vec<Self> = columns
+            .into_iter()
+            .zip(leaves)
+            .map(|(items, l)| Self::try_from_leaves(items, l).expect("failed to build tree"))
+            .collect();
+        let roots = trees.iter().map(Self::root).collect();
+        let combined = StaticMerkleArray::<H::Digest, H>::new(roots).root();
+        (trees, combined)
     }
 
-    /// Root commitment.
+    /// Root commitment: the top node passed through `H::finalize_root`
+    /// with the number of leaves.
     pub fn root(&self) -> H::Digest {
-        *self.levels.last().unwrap().first().unwrap()
+        H::finalize_root(
+            self.levels.last().unwrap().first().unwrap(),
+            self.leaf_count(),
+        )
+    }
+
+    /// Roots of every prefix: entry `k` is the root of a tree over
+    /// `items[..=k]`, as `new` would build it. Uses `IncrementalRoot`, so it
+    /// costs O(n log n) hashes instead of n rebuilds.
+    pub fn roots_of_prefixes(&self) -> Vec<H::Digest> {
+        let mut inc = incremental::IncrementalRoot::<H>::new();
+        self.levels[0][..self.len()]
+            .iter()
+            .map(|leaf| {
+                inc.push(*leaf);
+                inc.current_root().expect("non-empty")
+            })
+            .collect()
+    }
+
+    /// Commit to just the items at `keep`, for partial disclosure: returns
+    /// the root of a tree over their leaves (in `keep` order, as `new` would
+    /// build it) and, for each, its proof against this tree's root.
+    ///
+    /// The subset tree reuses the original leaf digests, so the proofs show
+    /// its leaves were committed by the full root; `verify_subset_root`
+    /// checks both links. Fails with `MerkleError::EmptyInput` if `keep` is
+    /// empty and `MerkleError::IndexOob` if an index is out of range.
+    pub fn subset_root(
+        &self,
+        keep: &[usize],
+    ) -> Result<(H::Digest, Vec<MerkleProof<H>>), MerkleError> {
+        let ctx = self.proof_context();
+        let proofs = keep
+            .iter()
+            .map(|&i| ctx.prove(i))
+            .collect::<Result<Vec<_>, _>>()?;
+        let root = subset_root_of(&proofs).ok_or(MerkleError::EmptyInput)?;
+        Ok((root, proofs))
     }
 
     /// Array length.
@@ -189,44 +1300,289 @@ where
         self.items.is_empty()
     }
 
+    /// Number of leaves, and the `tree_len` of every proof: `len()` plus any
+    /// empty slots added by `pad_to_depth`.
+    pub fn leaf_count(&self) -> usize {
+        self.items.len() + self.padding
+    }
+
+    /// Consume the structure and return the committed items.
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
     /// Build a proof of membership for a given index.
+    ///
+    /// In debug builds the proof is verified against `root()` before being
+    /// returned, so tree-construction bugs surface here rather than at the
+    /// verifier.
     pub fn prove_index(&self, index: usize) -> Result<MerkleProof<H>, MerkleError> {
-        if index >= self.len() {
+        let _span = trace_span!("prove_index", index);
+        let proof = self.proof_context().prove(index)?;
+        debug_assert!(
+            proof.verify_unmetered() && proof.root == self.root(),
+            "prove_index produced an invalid proof for index {index}"
+        );
+        Ok(proof)
+    }
+
+    /// Digest of internal node `node_index` at `level` (0 = leaves, up to the
+    /// unfinalized top node).
+    ///
+    /// Only real nodes are addressable (`node_index < ceil(leaf_count / 2^level)`),
+    /// not the duplicates added when padding odd levels; anything else yields
+    /// `MerkleError::IndexOob`.
+    pub fn subtree_root(&self, level: usize, node_index: usize) -> Result<H::Digest, MerkleError> {
+        if level >= self.levels.len() || node_index >= level_width(self.leaf_count(), level) {
+            return Err(MerkleError::IndexOob);
+        }
+        Ok(self.levels[level][node_index])
+    }
+
+    /// Authenticate the internal node `node_index` at `level` up to the root.
+    pub fn prove_subtree_root(
+        &self,
+        level: usize,
+        node_index: usize,
+    ) -> Result<SubtreeProof<H>, MerkleError> {
+        let node = self.subtree_root(level, node_index)?;
+        Ok(SubtreeProof {
+            level,
+            node_index,
+            tree_len: self.leaf_count(),
+            siblings: self.proof_context().path(level, node_index),
+            root: self.root(),
+            node,
+        })
+    }
+
+    /// `(level, node_index)` of each sibling on `index`'s authentication
+    /// path, bottom to top: the structure of `prove_index(index)` without the
+    /// digests, for cross-referencing against a flat node array.
+    ///
+    /// Indices are into the padded levels, so where a node is the last of an
+    /// odd level its sibling is the padding duplicate, at
+    /// `node_index == ceil(leaf_count / 2^level)`.
+    pub fn auth_path_indices(&self, index: usize) -> Result<Vec<(usize, usize)>, MerkleError> {
+        if index >= self.leaf_count() {
+            return Err(MerkleError::IndexOob);
+        }
+        Ok((0..self.levels.len() - 1)
+            .map(|level| (level, (index >> level) ^ 1))
+            .collect())
+    }
+
+    /// Lowest level at which the paths of leaves `i` and `j` meet, i.e. the
+    /// smallest `level` with `i >> level == j >> level` (0 when `i == j`).
+    ///
+    /// Their proofs share every sibling from that level up; at `level - 1`
+    /// each path's sibling is the other's ancestor. Index arithmetic only,
+    /// no hashing.
+    pub fn common_ancestor_level(&self, i: usize, j: usize) -> Result<usize, MerkleError> {
+        if i >= self.leaf_count() || j >= self.leaf_count() {
+            return Err(MerkleError::IndexOob);
+        }
+        Ok((usize::BITS - (i ^ j).leading_zeros()) as usize)
+    }
+
+    /// Cache, for every leaf, the offset of its sibling at each level, so
+    /// proofs are a gather over `levels` instead of walking the index.
+    ///
+    /// The table costs `4 * leaf_count * depth` bytes and is built on the first
+    /// proof (or `proof_context`), not here. Trees with more than
+    /// `u32::MAX` leaves, whose offsets don't fit, keep proving without it.
+    pub fn with_proof_index(mut self) -> Self {
+        self.sibling_offsets = Some(OnceLock::new());
+        self
+    }
+
+    /// The sibling offset table, building it if enabled and not built yet:
+    /// entry `index * depth + level` is the sibling's position in `levels[level]`.
+    fn sibling_offsets(&self) -> Option<&[u32]> {
+        let cell = self.sibling_offsets.as_ref()?;
+        u32::try_from(self.leaf_count()).ok()?;
+        let depth = self.levels.len() - 1;
+        Some(cell.get_or_init(|| {
+            (0..self.leaf_count())
+                .flat_map(|i| (0..depth).map(move |level| ((i >> level) ^ 1) as u32))
+                .collect()
+        }))
+    }
+
+    /// Borrow the tree for generating many proofs in a row.
+    pub fn proof_context(&self) -> ProofContext<'_, H> {
+        ProofContext {
+            levels: &self.levels,
+            len: self.leaf_count(),
+            root: self.root(),
+            salt: self.salt,
+            position_bound: self.position_bound,
+            offsets: self.sibling_offsets(),
+        }
+    }
+
+    /// Proofs for every index, in order.
+    pub fn prove_all(&self) -> Vec<MerkleProof<H>> {
+        let ctx = self.proof_context();
+        (0..self.len())
+            .map(|i| ctx.prove(i).expect("index in range"))
+            .collect()
+    }
+
+    /// Proofs for `indices`, generated lazily one at a time (unlike
+    /// `prove_all`, nothing is collected). Out-of-range indices yield
+    /// `MerkleError::IndexOob` in place, and the iterator carries on.
+    pub fn iter_proofs_for<'a, I>(
+        &'a self,
+        indices: I,
+    ) -> impl Iterator<Item = Result<MerkleProof<H>, MerkleError>> + 'a
+    where
+        I: IntoIterator<Item = usize>,
+        I::IntoIter: 'a,
+    {
+        let ctx = self.proof_context();
+        indices.into_iter().map(move |i| ctx.prove(i))
+    }
+
+    /// Integrity self-test: prove every index and check the proof against
+    /// `root()` and the leaf its stored item should have (`H::empty_leaf()`
+    /// for a padding slot), stopping at the first failure.
+    ///
+    /// O(n log n). Meant for after `load_from_file` or `update_leaf`, since it
+    /// exercises the same proof path that clients rely on. Unlike
+    /// `prove_index`, a corrupted tree returns `false` rather than tripping
+    /// the debug assertion.
+    pub fn verify_self_proofs(&self) -> bool {
+        let root = self.root();
+        let ctx = self.proof_context();
+        let expected = |i: usize| match self.items.get(i) {
+            Some(item) => self.leaf_at(item, i),
+            None => H::empty_leaf(),
+        };
+        (0..self.leaf_count()).all(|i| {
+            ctx.prove(i).is_ok_and(|proof| {
+                proof.root == root && proof.leaf == expected(i) && proof.verify_unmetered()
+            })
+        })
+    }
+
+    /// Build a single proof of membership for several indices.
+    ///
+    /// The input is normalized first (sorted and deduplicated), so duplicate
+    /// or out-of-order indices produce the same `MultiProof` as the canonical
+    /// set. Returns `MerkleError::IndexOob` if any index is out of range, or
+    /// `MerkleError::EmptyInput` if `indices` is empty.
+    pub fn prove_indices(&self, indices: &[usize]) -> Result<MultiProof<H>, MerkleError> {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.is_empty() {
+            return Err(MerkleError::EmptyInput);
+        }
+        if indices.iter().any(|&i| i >= self.leaf_count()) {
             return Err(MerkleError::IndexOob);
         }
-        let leaf = self.levels[0][index];
+
+        let leaves = indices.iter().map(|&i| self.levels[0][i]).collect();
         let mut siblings = Vec::new();
-        let mut i = index;
+        let mut known = indices.clone();
 
-        // For each level up to root
         for level in 0..self.levels.len() - 1 {
             let level_nodes = &self.levels[level];
-            let is_right = i % 2 == 1;
-            let sib_idx = if is_right { i - 1 } else { i + 1 }.min(level_nodes.len() - 1);
-            let sib = level_nodes[sib_idx];
-
-            // Record sibling + side
-            let side = if is_right { Side::Left } else { Side::Right };
-            siblings.push((sib, side));
-            i /= 2;
+            let mut next = Vec::with_capacity(known.len());
+            let mut k = 0;
+            while k < known.len() {
+                let i = known[k];
+                let sib_idx = i ^ 1;
+                if i.is_multiple_of(2) && k + 1 < known.len() && known[k + 1] == sib_idx {
+                    // Both children are known to the verifier.
+                    k += 1;
+                } else {
+                    siblings.push(level_nodes[sib_idx.min(level_nodes.len() - 1)]);
+                }
+                next.push(i / 2);
+                k += 1;
+            }
+            known = next;
         }
 
-        Ok(MerkleProof {
-            index,
+        Ok(MultiProof {
+            indices,
+            leaves,
             siblings,
+            depth: self.levels.len() - 1,
+            tree_len: self.leaf_count(),
             root: self.root(),
-            leaf,
+            salt: self.salt,
+            position_bound: self.position_bound,
         })
     }
 
     /// Return all positions of an item (works with duplicates).
     pub fn positions_of(&self, item: &T) -> Vec<usize> {
-        let leaf = H::leaf(item);
-        self.index_map.get(&leaf).cloned().unwrap_or_default()
+        self.positions_iter(item).collect()
+    }
+
+    /// Lazily yield the positions of an item, in ascending order, without
+    /// collecting them (e.g. `.next()` for just the first occurrence).
+    pub fn positions_iter(&self, item: &T) -> impl Iterator<Item = usize> + '_ {
+        // Position-bound leaves differ per index, so compare items instead.
+        let (by_digest, wanted) = if self.position_bound {
+            (&[][..], Some(item.clone()))
+        } else {
+            (self.positions_of_digest(&self.leaf_of(item)), None)
+        };
+        let scanned = wanted.into_iter().flat_map(move |wanted| {
+            self.items
+                .iter()
+                .enumerate()
+                .filter(move |(_, x)| **x == wanted)
+                .map(|(i, _)| i)
+        });
+        by_digest.iter().copied().chain(scanned)
+    }
+
+    /// The leaf digest `item` has in this tree, salt included. Position-bound
+    /// leaves depend on the index too; see `leaf_at`.
+    pub fn leaf_of(&self, item: &T) -> H::Digest {
+        if self.empty_item.as_ref() == Some(item) {
+            return H::empty_leaf();
+        }
+        leaf_digest::<H, T>(&self.salt, None, item)
+    }
+
+    /// The leaf digest `item` would have at `index` in this tree, salt and
+    /// position binding included.
+    pub fn leaf_at(&self, item: &T, index: usize) -> H::Digest {
+        if self.empty_item.as_ref() == Some(item) {
+            return H::empty_leaf();
+        }
+        leaf_digest::<H, T>(&self.salt, self.position_bound.then_some(index), item)
+    }
+
+    /// Was the tree built with `new_position_bound`?
+    pub fn is_position_bound(&self) -> bool {
+        self.position_bound
+    }
+
+    /// The salt mixed into every leaf, if built with `new_salted`.
+    pub fn salt(&self) -> Option<&Salt> {
+        self.salt.as_ref()
+    }
+
+    /// Return all positions whose leaf hash is `leaf` (no re-hashing).
+    pub fn positions_of_digest(&self, leaf: &H::Digest) -> &[usize] {
+        self.index_map
+            .get(leaf)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
     }
 
     /// Build a proof for a given item (by value).
-    /// If it occurs multiple times, use `occurrence` to disambiguate.
+    /// If it occurs multiple times, use `occurrence` (0-based) to disambiguate.
+    ///
+    /// Returns `MerkleError::NotFound` if the item is absent and
+    /// `MerkleError::OccurrenceOob` if it occurs fewer than `occurrence + 1` times.
     pub fn prove_item(
         &self,
         item: &T,
@@ -238,131 +1594,728 @@ where
         }
         let idx = occurrence.unwrap_or(0);
         if idx >= poss.len() {
-            return Err(MerkleError::NotFound);
+            return Err(MerkleError::OccurrenceOob {
+                found: poss.len(),
+                requested: idx,
+            });
         }
         self.prove_index(poss[idx])
     }
 
-    /// Save the full structure to a file (binary encoding).
-    pub fn save_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), MerkleError> {
-        let bytes = bincode::serialize(self)?;
-        std::fs::write(path, bytes)?;
-        Ok(())
-    }
-
-    /// Load a structure from a file previously saved with `save_to_file`.
-    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, MerkleError> {
-        let mut file = fs::File::open(path)?;
-        let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes)?;
-        let me: Self = bincode::deserialize(&bytes)?;
-        Ok(me)
+    /// One multi-proof covering every position of `item`, sharing siblings
+    /// between the occurrences. Check it with `verify_value_multi`.
+    ///
+    /// Returns `MerkleError::NotFound` if the item is absent.
+    pub fn prove_value_multi(&self, item: &T) -> Result<MultiProof<H>, MerkleError> {
+        let positions = self.positions_of(item);
+        if positions.is_empty() {
+            return Err(MerkleError::NotFound);
+        }
+        self.prove_indices(&positions)
     }
-}
-
-/* -------------------------------------------------------------------------
-Convenience
-------------------------------------------------------------------------- */
 
-/// Verify that a value belongs to the commitment, using its proof.
-pub fn verify_value_with_proof<T, H>(value: &T, proof: &MerkleProof<H>) -> bool
-where
-    T: Serialize + DeserializeOwned,
-    H: MerkleHasher,
-{
-    H::leaf(value) == proof.leaf && proof.verify()
-}
+    /// Rebuild with some items replaced, returning the new structure and the
+    /// sorted leaf indices whose leaf hash actually changed.
+    ///
+    /// The structure itself is static: this clones `items`, applies `changes`
+    /// in order (later entries for the same index win) and builds a fresh tree,
+    /// padded like this one.
+    pub fn rebuild_with_replacements(
+        &self,
+        changes: &[(usize, T)],
+    ) -> Result<(Self, Vec<usize>), MerkleError> {
+        if changes.iter().any(|(i, _)| *i >= self.len()) {
+            return Err(MerkleError::IndexOob);
+        }
+        let mut items = self.items.clone();
+        for (i, item) in changes {
+            items[*i] = item.clone();
+        }
+        let mut rebuilt = Self::try_build(
+            items,
+            self.salt,
+            self.position_bound,
+            self.empty_item.clone(),
+        )?;
+        if self.padding > 0 {
+            rebuilt = rebuilt.try_into_fixed_depth(self.levels.len() - 1)?;
+        }
 
-/* ------------------------------- Tests ---------------------------------- */
+        let mut changed: Vec<usize> = changes
+            .iter()
+            .map(|(i, _)| *i)
+            .filter(|&i| rebuilt.levels[0][i] != self.levels[0][i])
+            .collect();
+        changed.sort_unstable();
+        changed.dedup();
+        Ok((rebuilt, changed))
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rand::Rng;
-    use sha2::{Digest, Sha256};
+    /// Replace `items[index]` with `new_item`, recomputing only the path from
+    /// its leaf to the root (O(log n) hashes instead of a rebuild).
+    ///
+    /// Proofs made before the update no longer verify against `root()`.
+    pub fn update_leaf(&mut self, index: usize, new_item: T) -> Result<(), MerkleError> {
+        if index >= self.len() {
+            return Err(MerkleError::IndexOob);
+        }
+        let len = self.leaf_count();
+        let old_leaf = self.levels[0][index];
+        let new_leaf = self.leaf_at(&new_item, index);
+        self.items[index] = new_item;
 
-    /// A 32-byte digest newtype so we can `Debug` as hex and use as map keys.
-    #[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-    pub struct Hash32([u8; 32]);
+        let mut acc = new_leaf;
+        let mut i = index;
+        let top = self.levels.len() - 1;
+        for level in 0..top {
+            let nodes = &mut self.levels[level];
+            nodes[i] = acc;
+            // The last node of an odd level is duplicated as padding.
+            if i + 1 == level_width(len, level) && nodes.len() > i + 1 {
+                nodes[i + 1] = acc;
+            }
+            acc = H::node(&nodes[i & !1], &nodes[i | 1]);
+            i /= 2;
+        }
+        self.levels[top][0] = acc;
 
-    impl std::fmt::Debug for Hash32 {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            for b in &self.0 {
-                write!(f, "{:02x}", b)?;
+        if old_leaf != new_leaf {
+            if let Some(positions) = self.index_map.get_mut(&old_leaf) {
+                positions.retain(|&p| p != index);
+                if positions.is_empty() {
+                    self.index_map.remove(&old_leaf);
+                }
             }
-            Ok(())
+            let positions = self.index_map.entry(new_leaf).or_default();
+            let at = positions.partition_point(|&p| p < index);
+            positions.insert(at, index);
         }
+        Ok(())
     }
 
-    fn sha256(bytes: &[u8]) -> Hash32 {
-        let mut h = Sha256::new();
-        h.update(bytes);
-        let out = h.finalize();
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&out);
-        Hash32(arr)
+    /// Recompute `index_map` from the leaf level, e.g. after loading a tree
+    /// whose saved map is stale or was edited by hand. `positions_of`,
+    /// `positions_of_digest` and `prove_item` read the map, so they return
+    /// wrong positions until it matches the leaves again.
+    pub fn rebuild_index_map(&mut self) {
+        let mut idx: HashMap<H::Digest, Vec<usize>> = HashMap::with_capacity(self.len());
+        // `levels[0]` may end with a padding copy of the last leaf; skip it.
+        for (i, leaf) in self.levels[0][..self.len()].iter().enumerate() {
+            idx.entry(*leaf).or_default().push(i);
+        }
+        self.index_map = idx;
     }
 
-    /// Domain separation tags for leaves and nodes.
-    const LEAF_TAG: u8 = 0x00;
-    const NODE_TAG: u8 = 0x01;
-
-    /// Default SHA-256 hasher with the same encoding as before.
-    #[derive(Debug, Clone, Copy, Default)]
-    pub struct Sha256Hasher;
+    /// Pad the tree with `H::empty_leaf()` slots up to exactly `2^depth`
+    /// leaves, so every proof has exactly `depth` siblings (as fixed-depth
+    /// circuits require).
+    ///
+    /// The items and their leaves stay where they are, so their proofs still
+    /// verify with `verify_value_with_proof`, and `update_leaf`,
+    /// `positions_of` and `prove_item` work as before. The padding slots hold
+    /// no item: `len()` is unchanged, `leaf_count()` is `2^depth`, and a
+    /// padding slot's proof has `leaf == H::empty_leaf()`. Existing leaf
+    /// digests are reused rather than rehashed. Returns
+    /// `MerkleError::TooLarge` if the tree has more than `2^depth` items.
+    pub fn try_into_fixed_depth(self, depth: usize) -> Result<Self, MerkleError> {
+        let leaves = self.levels.into_iter().next().unwrap_or_default();
+        let mut padded = Self::pad_leaves(self.items, leaves, depth)?;
+        padded.salt = self.salt;
+        padded.position_bound = self.position_bound;
+        padded.empty_item = self.empty_item;
+        Ok(padded)
+    }
 
-    impl MerkleHasher for Sha256Hasher {
-        type Digest = Hash32;
+    /// Like `try_into_fixed_depth`, but leaves `self` untouched.
+    pub fn pad_to_depth(&self, depth: usize) -> Result<Self, MerkleError> {
+        let mut padded = Self::pad_leaves(self.items.clone(), self.levels[0].clone(), depth)?;
+        padded.salt = self.salt;
+        padded.position_bound = self.position_bound;
+        padded.empty_item = self.empty_item.clone();
+        Ok(padded)
+    }
 
-        fn leaf<T: Serialize>(item: &T) -> Self::Digest {
-            let enc = bincode::serialize(item).expect("bincode serialize");
-            let mut buf = Vec::with_capacity(1 + enc.len());
-            buf.push(LEAF_TAG);
-            buf.extend_from_slice(&enc);
-            sha256(&buf)
+    /// Shared body of `try_into_fixed_depth` / `pad_to_depth`; `leaves` may
+    /// still carry padding. The callers carry the salt, position binding and
+    /// empty item over, so the padded tree hashes items like the original.
+    fn pad_leaves(
+        items: Vec<T>,
+        mut leaves: Vec<H::Digest>,
+        depth: usize,
+    ) -> Result<Self, MerkleError> {
+        let len = items.len();
+        // Depths past the address space fail below, when reserving the slots.
+        let width = u32::try_from(depth)
+            .ok()
+            .and_then(|d| 1usize.checked_shl(d))
+            .unwrap_or(usize::MAX);
+        if len > width {
+            return Err(MerkleError::TooLarge { len, max: width });
         }
 
-        fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
-            let mut buf = [0u8; 1 + 32 + 32];
-            buf[0] = NODE_TAG;
-            buf[1..33].copy_from_slice(&left.0);
-            buf[33..].copy_from_slice(&right.0);
-            sha256(&buf)
-        }
+        leaves.truncate(len);
+        leaves.try_reserve_exact(width - len + 1)?;
+        leaves.resize(width, H::empty_leaf());
+        Self::try_from_leaves(items, leaves)
     }
 
-    type ShaSMA<T> = StaticMerkleArray<T, Sha256Hasher>;
+    /// Save the full structure to a file (versioned binary encoding, see
+    /// `FORMAT_VERSION`).
+    pub fn save_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), MerkleError> {
+        write_versioned(path, self)
+    }
 
-    #[test]
-    fn build_and_root() {
-        let arr: Vec<u64> = (0..10).collect();
-        let sm = ShaSMA::new(arr.clone());
-        assert_eq!(sm.len(), 10);
-        let root = sm.root();
-        // Root should be stable for same data
-        let sm2 = ShaSMA::new(arr);
-        assert_eq!(root, sm2.root());
+    /// Load a structure from a file previously saved with `save_to_file`.
+    ///
+    /// Fails with `MerkleError::VersionMismatch` for files of another format
+    /// version, including unversioned files from before the header existed.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, MerkleError> {
+        read_versioned(path)
+    }
+}
+
+impl<T, H> StaticMerkleArray<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone + Ord,
+    H: MerkleHasher,
+{
+    /// Sort `items` and build the structure over the sorted order.
+    pub fn new_sorted(mut items: Vec<T>) -> Self {
+        items.sort();
+        Self::new(items)
     }
 
-    #[test]
-    fn prove_and_verify_by_index() {
-        let arr: Vec<u64> = (0..16).collect();
-        let sm = ShaSMA::new(arr.clone());
+    /// Number of committed items `<= value`, by binary search.
+    ///
+    /// Only meaningful for a tree built over sorted items (`new_sorted`);
+    /// on an unsorted tree the result is unspecified.
+    pub fn rank(&self, value: &T) -> usize {
+        self.items.partition_point(|x| x <= value)
+    }
+}
 
-        for i in 0..arr.len() {
-            let proof = sm.prove_index(i).unwrap();
-            assert!(proof.verify());
-            assert!(verify_value_with_proof(&arr[i], &proof));
+impl<T, H> StaticMerkleArray<Option<T>, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    /// Build a tree with exactly `width` slots, where `None` slots (and any
+    /// slots past the end of `items`) commit to `H::empty_leaf()`.
+    ///
+    /// `Some(x)` slots commit to `H::leaf(&Some(x))`, so their proofs verify
+    /// with `verify_value_with_proof(&Some(x), ..)`. A `None` slot's proof has
+    /// `leaf == H::empty_leaf()`; check it with `proof.verify()`. The tree
+    /// remembers that `None` is the empty item, so `update_leaf(i, None)`
+    /// empties a slot and `positions_of(&None)` finds the empty ones.
+    ///
+    /// Returns `MerkleError::EmptyInput` if `width` is zero and
+    /// `MerkleError::TooLarge` if `items` has more than `width` entries.
+    pub fn new_fixed_width(mut items: Vec<Option<T>>, width: usize) -> Result<Self, MerkleError> {
+        if width == 0 {
+            return Err(MerkleError::EmptyInput);
+        }
+        if items.len() > width {
+            return Err(MerkleError::TooLarge {
+                len: items.len(),
+                max: width,
+            });
         }
+        items.try_reserve_exact(width - items.len())?;
+        items.resize(width, None);
+        Self::try_build(items, None, false, Some(None))
     }
+}
 
-    #[test]
-    fn prove_and_verify_by_value_with_duplicates() {
-        // Array with duplicates
-        let arr = vec![7u32, 1, 7, 2, 7, 3, 4, 7];
-        let sm = ShaSMA::new(arr.clone());
-
-        // Find all positions of 7
+impl<T, H> From<StaticMerkleArray<T, H>> for Vec<T>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    fn from(sm: StaticMerkleArray<T, H>) -> Self {
+        sm.into_items()
+    }
+}
+
+impl<H: MerkleHasher> StaticMerkleArray<String, H> {
+    /// Build from a text file, committing each line as a leaf.
+    ///
+    /// Lines are split on `\n` (a preceding `\r` is stripped); a trailing
+    /// newline does not produce an empty final leaf. Returns
+    /// `MerkleError::EmptyInput` if the file contains no lines.
+    pub fn from_lines_file<P: AsRef<Path>>(path: P) -> Result<Self, MerkleError> {
+        let text = fs::read_to_string(&path).map_err(io_err(&path))?;
+        let lines: Vec<String> = text.lines().map(str::to_owned).collect();
+        if lines.is_empty() {
+            return Err(MerkleError::EmptyInput);
+        }
+        Ok(Self::new(lines))
+    }
+}
+
+impl<H: MerkleHasher> StaticMerkleArray<Vec<u8>, H> {
+    /// Commit to `data` split into `chunk_size`-byte chunks, one leaf per
+    /// chunk (the last chunk may be shorter).
+    ///
+    /// Panics if `data` is empty or `chunk_size` is zero.
+    pub fn from_chunks(data: &[u8], chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+        Self::new(data.chunks(chunk_size).map(<[u8]>::to_vec).collect())
+    }
+
+    /// Prove the chunks covering the byte range `start..end` of the original
+    /// data, as a `MultiProof` over their chunk indices.
+    ///
+    /// The chunk size is taken from the first chunk, so this assumes the tree
+    /// was built with `from_chunks`. Returns `MerkleError::NotFound` for an
+    /// empty range and `MerkleError::IndexOob` if `end` is past the data.
+    pub fn prove_byte_range(&self, start: usize, end: usize) -> Result<MultiProof<H>, MerkleError> {
+        if start >= end {
+            return Err(MerkleError::NotFound);
+        }
+        let total: usize = self.items.iter().map(Vec::len).sum();
+        if end > total {
+            return Err(MerkleError::IndexOob);
+        }
+        let chunk_size = self.items[0].len();
+        let chunks: Vec<usize> = (start / chunk_size..=(end - 1) / chunk_size).collect();
+        self.prove_indices(&chunks)
+    }
+}
+
+/// Borrowed view of a tree's levels for batch proof generation.
+///
+/// Created by `StaticMerkleArray::proof_context`; the root and level borrows
+/// are set up once and shared by every `prove` call.
+#[derive(Debug, Clone, Copy)]
+pub struct ProofContext<'a, H: MerkleHasher> {
+    levels: &'a [Vec<H::Digest>],
+    len: usize,
+    root: H::Digest,
+    salt: Option<Salt>,
+    position_bound: bool,
+    offsets: Option<&'a [u32]>,
+}
+
+impl<H: MerkleHasher> ProofContext<'_, H> {
+    /// Build a proof of membership for a given index.
+    pub fn prove(&self, index: usize) -> Result<MerkleProof<H>, MerkleError> {
+        if index >= self.len {
+            return Err(MerkleError::IndexOob);
+        }
+        Ok(MerkleProof {
+            index,
+            tree_len: self.len,
+            siblings: match self.offsets {
+                Some(offsets) => self.gather(offsets, index),
+                None => self.path(0, index),
+            },
+            root: self.root,
+            leaf: self.levels[0][index],
+            salt: self.salt,
+            position_bound: self.position_bound,
+        })
+    }
+
+    /// `path(0, index)` from the `with_proof_index` table: the sibling is on
+    /// the left exactly when its offset is even.
+    fn gather(&self, offsets: &[u32], index: usize) -> Vec<(H::Digest, Side)> {
+        let depth = self.levels.len() - 1;
+        offsets[index * depth..(index + 1) * depth]
+            .iter()
+            .zip(self.levels)
+            .map(|(&offset, nodes)| {
+                let side = if offset % 2 == 0 {
+                    Side::Left
+                } else {
+                    Side::Right
+                };
+                (nodes[offset as usize], side)
+            })
+            .collect()
+    }
+
+    /// Siblings from node `index` of `level` up to the root.
+    fn path(&self, level: usize, index: usize) -> Vec<(H::Digest, Side)> {
+        let upper = &self.levels[level..self.levels.len() - 1];
+        let mut siblings = Vec::with_capacity(upper.len());
+        let mut i = index;
+
+        // For each level up to root
+        for level_nodes in upper {
+            let is_right = i % 2 == 1;
+            // `i ^ 1` can't overflow, unlike `i + 1`; padded levels always
+            // hold it, the clamp is only a safety net.
+            let sib_idx = (i ^ 1).min(level_nodes.len() - 1);
+            let sib = level_nodes[sib_idx];
+
+            // Record sibling + side
+            let side = if is_right { Side::Left } else { Side::Right };
+            siblings.push((sib, side));
+            i /= 2;
+        }
+        siblings
+    }
+}
+
+/* -------------------------------------------------------------------------
+Convenience
+------------------------------------------------------------------------- */
+
+/// Build a tree over `items`, prove `index`, and drop the tree: returns the
+/// root and the proof.
+///
+/// Fails with `MerkleError::IndexOob` (before hashing anything) if `index`
+/// is out of range, and otherwise as `StaticMerkleArray::try_new` does.
+pub fn commit_and_prove<T, H>(
+    items: Vec<T>,
+    index: usize,
+) -> Result<(H::Digest, MerkleProof<H>), MerkleError>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    if index >= items.len() && !items.is_empty() {
+        return Err(MerkleError::IndexOob);
+    }
+    let tree = StaticMerkleArray::<T, H>::try_new(items)?;
+    Ok((tree.root(), tree.prove_index(index)?))
+}
+
+/// Verify that a value belongs to the commitment, using its proof.
+pub fn verify_value_with_proof<T, H>(value: &T, proof: &MerkleProof<H>) -> bool
+where
+    T: Serialize + DeserializeOwned,
+    H: MerkleHasher,
+{
+    proof.leaf_for(value) == proof.leaf && proof.verify()
+}
+
+/// Verify that `value` sits at every index of a multi-proof (see
+/// `StaticMerkleArray::prove_value_multi`). This shows where the value
+/// occurs, not that it occurs nowhere else.
+pub fn verify_value_multi<T, H>(value: &T, proof: &MultiProof<H>) -> bool
+where
+    T: Serialize,
+    H: MerkleHasher,
+{
+    let position = |i: usize| proof.position_bound.then_some(i);
+    proof.indices.len() == proof.leaves.len()
+        && (proof.indices.iter().zip(&proof.leaves))
+            .all(|(&i, l)| *l == leaf_digest::<H, T>(&proof.salt, position(i), value))
+        && proof.verify()
+}
+
+/// Like `verify_value_with_proof`, but say why verification failed.
+///
+/// A `LeafMismatch` means `value` (as encoded by `H::leaf`) is not what was
+/// committed, e.g. the tree was built over a canonical form of it; a
+/// `RootMismatch` means the leaf is right but the path or root is not.
+pub fn verify_value_with_proof_explain<T, H>(
+    value: &T,
+    proof: &MerkleProof<H>,
+) -> Result<(), VerifyError<H::Digest>>
+where
+    T: Serialize,
+    H: MerkleHasher,
+{
+    if !proof.is_valid_structure() {
+        return Err(VerifyError::Shape {
+            index: proof.index,
+            tree_len: proof.tree_len,
+            depth: proof.siblings.len(),
+        });
+    }
+    let leaf = proof.leaf_for(value);
+    if leaf != proof.leaf {
+        return Err(VerifyError::LeafMismatch {
+            computed: leaf,
+            expected: proof.leaf,
+        });
+    }
+    let root = H::finalize_root(&proof.recompute_root(), proof.tree_len);
+    if root != proof.root {
+        return Err(VerifyError::RootMismatch {
+            computed: root,
+            expected: proof.root,
+        });
+    }
+    Ok(())
+}
+
+/// Check everything a light client needs in one call: `proof` is for
+/// `index`, carries the trusted `expected_root`, commits to `item`, and
+/// verifies. Each failure is reported separately.
+pub fn authenticate<T, H>(
+    item: &T,
+    index: usize,
+    proof: &MerkleProof<H>,
+    expected_root: &H::Digest,
+) -> Result<(), AuthError<H::Digest>>
+where
+    T: Serialize,
+    H: MerkleHasher,
+{
+    let meter = meter::Meter::start("authenticate");
+    let result = authenticate_unmetered(item, index, proof, expected_root);
+    meter.finish(result.is_ok());
+    result
+}
+
+fn authenticate_unmetered<T, H>(
+    item: &T,
+    index: usize,
+    proof: &MerkleProof<H>,
+    expected_root: &H::Digest,
+) -> Result<(), AuthError<H::Digest>>
+where
+    T: Serialize,
+    H: MerkleHasher,
+{
+    if proof.index != index {
+        return Err(AuthError::IndexMismatch {
+            expected: index,
+            found: proof.index,
+        });
+    }
+    if proof.root != *expected_root {
+        return Err(AuthError::UntrustedRoot {
+            expected: *expected_root,
+            found: proof.root,
+        });
+    }
+    Ok(verify_value_with_proof_explain(item, proof)?)
+}
+
+/// "Position `expected_index` holds `expected_item` in the commitment `root`":
+/// `authenticate` as a plain yes/no, with the arguments in query order.
+///
+/// True only if the proof's leaf is `expected_item`'s, its index is
+/// `expected_index`, its root is `root`, and the path recomputes that root.
+pub fn authenticate_at<T, H>(
+    proof: &MerkleProof<H>,
+    expected_item: &T,
+    expected_index: usize,
+    root: &H::Digest,
+) -> bool
+where
+    T: Serialize,
+    H: MerkleHasher,
+{
+    authenticate(expected_item, expected_index, proof, root).is_ok()
+}
+
+/// Verify an item against a root from loose proof parts, without building a
+/// `MerkleProof`. Same checks as `verify_value_with_proof`, including that
+/// the sides of `siblings` spell out `index`, so the item is authenticated at
+/// that position and no other.
+///
+/// `tree_len` is needed as well as `index`: the root is
+/// `H::finalize_root(top, tree_len)`, which some hashers bind the length
+/// into, and only `tree_len` fixes how many siblings a genuine path has.
+pub fn verify_from_parts<T, H>(
+    item: &T,
+    index: usize,
+    tree_len: usize,
+    siblings: &[(H::Digest, Side)],
+    root: &H::Digest,
+) -> bool
+where
+    T: Serialize,
+    H: MerkleHasher,
+{
+    if !path_matches(index, tree_len, siblings) {
+        return false;
+    }
+    H::finalize_root(&fold_path::<H>(&H::leaf(item), siblings), tree_len) == *root
+}
+
+/// Fold `leaf` through borrowed `siblings` and compare with `root`, without
+/// building a `MerkleProof` or allocating.
+///
+/// This is the bare path check under `MerkleProof::verify`: `root` is the top
+/// digest *before* `finalize_root` (the published root for hashers with the
+/// default identity finalization), and the number of siblings is not checked
+/// against a tree length. Use `verify_from_parts` when you have `tree_len`.
+pub fn verify_with_siblings<H: MerkleHasher>(
+    leaf: &H::Digest,
+    siblings: &[(H::Digest, Side)],
+    root: &H::Digest,
+) -> bool {
+    fold_path::<H>(leaf, siblings) == *root
+}
+
+/// Check a batch of expected `(index, value, proof)` entries against a
+/// trusted `root`, without the tree itself.
+///
+/// Each entry must have `proof.index == index`, `proof.root == *root`, a leaf
+/// equal to `H::leaf(value)`, and a proof that verifies. Returns the `index`
+/// of the first entry that fails.
+pub fn verify_expectations<T, H>(
+    root: &H::Digest,
+    items: &[(usize, T, MerkleProof<H>)],
+) -> Result<(), usize>
+where
+    T: Serialize + DeserializeOwned,
+    H: MerkleHasher,
+{
+    for (index, value, proof) in items {
+        if proof.index != *index || proof.root != *root || !verify_value_with_proof(value, proof) {
+            return Err(*index);
+        }
+    }
+    Ok(())
+}
+
+/// Root of a tree over the proofs' leaves, in order (`None` if empty).
+fn subset_root_of<H: MerkleHasher>(proofs: &[MerkleProof<H>]) -> Option<H::Digest> {
+    let mut inc = incremental::IncrementalRoot::<H>::new();
+    for proof in proofs {
+        inc.push(proof.leaf);
+    }
+    inc.current_root()
+}
+
+/// Check a commitment from `StaticMerkleArray::subset_root`: every proof
+/// verifies against `original_root`, and `subset_root` is the root over the
+/// proofs' leaves in order. The verifier learns the kept leaves and their
+/// positions, nothing about the rest.
+pub fn verify_subset_root<H: MerkleHasher>(
+    subset_root: &H::Digest,
+    original_root: &H::Digest,
+    proofs: &[MerkleProof<H>],
+) -> bool {
+    proofs
+        .iter()
+        .all(|p| p.root == *original_root && p.verify())
+        && subset_root_of(proofs).is_some_and(|root| root == *subset_root)
+}
+
+/// Roots of all-zero subtrees: `[zero, node(zero, zero), ...]` for heights
+/// `0..=depth` (so the result has `depth + 1` entries).
+pub fn zero_subtree_roots<H>(depth: usize) -> Vec<H::Digest>
+where
+    H: MerkleHasher,
+    H::Digest: Default,
+{
+    let mut out = Vec::with_capacity(depth + 1);
+    let mut cur = H::zero_digest();
+    out.push(cur);
+    for _ in 0..depth {
+        cur = H::node(&cur, &cur);
+        out.push(cur);
+    }
+    out
+}
+
+/// Offset of the first byte where two roots differ, or `None` if they're
+/// equal. A debugging aid: a mismatch only at the very end often means the
+/// other side's digest is the same value in the opposite byte order (compare
+/// with `mimc_bn254_hasher::digest_to_be_bytes` for MiMC roots).
+pub fn root_diff_byte(a: &[u8; 32], b: &[u8; 32]) -> Option<usize> {
+    a.iter().zip(b).position(|(x, y)| x != y)
+}
+
+/* ------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use sha2::{Digest, Sha256};
+
+    /// A 32-byte digest newtype so we can `Debug` as hex and use as map keys.
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub struct Hash32([u8; 32]);
+
+    impl std::fmt::Debug for Hash32 {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for b in &self.0 {
+                write!(f, "{:02x}", b)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl AsRef<[u8]> for Hash32 {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    fn sha256(bytes: &[u8]) -> Hash32 {
+        let mut h = Sha256::new();
+        h.update(bytes);
+        let out = h.finalize();
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&out);
+        Hash32(arr)
+    }
+
+    /// Domain separation tags for leaves and nodes.
+    const LEAF_TAG: u8 = 0x00;
+    const NODE_TAG: u8 = 0x01;
+
+    /// Default SHA-256 hasher with the same encoding as before.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Sha256Hasher;
+
+    impl MerkleHasher for Sha256Hasher {
+        type Digest = Hash32;
+
+        fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+            Self::try_leaf(item).expect("bincode serialize")
+        }
+
+        fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+            let enc = bincode::serialize(item)?;
+            let mut buf = Vec::with_capacity(1 + enc.len());
+            buf.push(LEAF_TAG);
+            buf.extend_from_slice(&enc);
+            Ok(sha256(&buf))
+        }
+
+        fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+            let mut buf = [0u8; 1 + 32 + 32];
+            buf[0] = NODE_TAG;
+            buf[1..33].copy_from_slice(&left.0);
+            buf[33..].copy_from_slice(&right.0);
+            sha256(&buf)
+        }
+    }
+
+    type ShaSMA<T> = StaticMerkleArray<T, Sha256Hasher>;
+
+    #[test]
+    fn build_and_root() {
+        let arr: Vec<u64> = (0..10).collect();
+        let sm = ShaSMA::new(arr.clone());
+        assert_eq!(sm.len(), 10);
+        let root = sm.root();
+        // Root should be stable for same data
+        let sm2 = ShaSMA::new(arr);
+        assert_eq!(root, sm2.root());
+    }
+
+    #[test]
+    #[allow(clippy::needless_range_loop)]
+    fn prove_and_verify_by_index() {
+        let arr: Vec<u64> = (0..16).collect();
+        let sm = ShaSMA::new(arr.clone());
+
+        for i in 0..arr.len() {
+            let proof = sm.prove_index(i).unwrap();
+            assert!(proof.verify());
+            assert!(verify_value_with_proof(&arr[i], &proof));
+        }
+    }
+
+    #[test]
+    fn prove_and_verify_by_value_with_duplicates() {
+        // Array with duplicates
+        let arr = vec![7u32, 1, 7, 2, 7, 3, 4, 7];
+        let sm = ShaSMA::new(arr.clone());
+
+        // Find all positions of 7
         let pos = sm.positions_of(&7);
         assert_eq!(pos, vec![0, 2, 4, 7]);
 
@@ -374,6 +2327,47 @@ mod tests {
         let p2 = sm.prove_item(&7, Some(2)).unwrap(); // third
         assert!(verify_value_with_proof(&7, &p2));
         assert_eq!(p2.index, 4);
+
+        assert!(matches!(
+            sm.prove_item(&7, Some(5)),
+            Err(MerkleError::OccurrenceOob {
+                found: 4,
+                requested: 5
+            })
+        ));
+        assert!(matches!(
+            sm.prove_item(&9, None),
+            Err(MerkleError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn positions_iter_yields_positions_lazily() {
+        let arr = vec![7u32, 1, 7, 2, 7, 3, 4, 7];
+        let sm = ShaSMA::new(arr);
+        assert_eq!(
+            sm.positions_iter(&7).collect::<Vec<_>>(),
+            sm.positions_of(&7)
+        );
+        assert_eq!(
+            sm.positions_iter(&7).take(2).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert_eq!(sm.positions_iter(&9).next(), None);
+    }
+
+    #[test]
+    fn positions_of_digest_matches_positions_of() {
+        let arr = vec![7u32, 1, 7, 2, 7, 3, 4, 7];
+        let sm = ShaSMA::new(arr);
+        assert_eq!(
+            sm.positions_of_digest(&Sha256Hasher::leaf(&7u32)),
+            &sm.positions_of(&7)[..]
+        );
+        assert_eq!(sm.positions_of_digest(&Sha256Hasher::leaf(&1u32)), &[1]);
+        assert!(sm
+            .positions_of_digest(&Sha256Hasher::leaf(&9u32))
+            .is_empty());
     }
 
     #[test]
@@ -394,22 +2388,1336 @@ mod tests {
     }
 
     #[test]
-    fn proof_persistence_roundtrip() {
+    fn into_items_recovers_the_array() {
         let arr: Vec<u64> = (0..25).collect();
-        let sm = ShaSMA::new(arr.clone());
-        let root_before = sm.root();
+        assert_eq!(ShaSMA::new(arr.clone()).into_items(), arr);
 
-        let path = std::env::temp_dir().join("sma.bin");
-        
+        // Also after a save/load roundtrip, through `From`.
+        let path = std::env::temp_dir().join(format!("sma_items_{}.bin", std::process::id()));
+        ShaSMA::new(arr.clone()).save_to_file(&path).unwrap();
+        let loaded: ShaSMA<u64> = ShaSMA::load_from_file(&path).unwrap();
+        let items: Vec<u64> = loaded.into();
+        assert_eq!(items, arr);
+        let _ = std::fs::remove_file(&path);
+    }
 
-        // Proof from loaded still verifies
-        let proof = sm.prove_index(13).unwrap();
+    #[test]
+    fn prefix_roots_match_rebuilds() {
+        let arr: Vec<u64> = (0..19).collect();
+        let roots = StaticMerkleArray::<u64, LenBoundHasher>::new(arr.clone()).roots_of_prefixes();
+        assert_eq!(roots.len(), arr.len());
+        for (k, root) in roots.iter().enumerate() {
+            let prefix = StaticMerkleArray::<u64, LenBoundHasher>::new(arr[..=k].to_vec());
+            assert_eq!(*root, prefix.root(), "prefix of {}", k + 1);
+        }
+    }
 
-        proof.save_to_file(&path).unwrap();
-        let loaded_proof = MerkleProof::<Sha256Hasher>::load_from_file(&path).unwrap();
-        assert_eq!(loaded_proof.get_merkle_root(), root_before);
-        assert!(verify_value_with_proof(&arr[13], &proof));
-        assert!(verify_value_with_proof(&arr[13], &loaded_proof));
+    #[test]
+    fn power_of_two_boundaries() {
+        for len in [1usize, 2, 3, 16, 17, 32, 33, 1024, 1025] {
+            let arr: Vec<u64> = (0..len as u64).collect();
+            let sm = ShaSMA::new(arr.clone());
+            let ctx = sm.proof_context();
+            for (i, v) in arr.iter().enumerate() {
+                let p = ctx.prove(i).unwrap();
+                assert_eq!(p.siblings.len(), depth_for_len(len), "len {len}");
+                assert!(verify_value_with_proof(v, &p), "len {len}, index {i}");
+            }
+            assert!(matches!(sm.prove_index(len), Err(MerkleError::IndexOob)));
+            assert!(matches!(
+                sm.prove_index(usize::MAX),
+                Err(MerkleError::IndexOob)
+            ));
+        }
+    }
+
+    #[test]
+    fn index_bits_agree_with_sides() {
+        let sm = ShaSMA::<u64>::new((0..13).collect());
+        for i in 0..sm.len() {
+            let p = sm.prove_index(i).unwrap();
+            let from_sides: Vec<bool> = p.siblings.iter().map(|(_, s)| *s == Side::Left).collect();
+            assert_eq!(p.index_bits(), from_sides, "index {i}");
+        }
+        assert_eq!(
+            sm.prove_index(6).unwrap().index_bits(),
+            vec![false, true, true, false]
+        );
+    }
+
+    #[test]
+    fn columns_match_separate_trees() {
+        let ids: Vec<u64> = (0..7).collect();
+        let scores: Vec<u64> = (0..7).map(|i| i * i).collect();
+        let (trees, combined) = ShaSMA::new_columns(vec![ids.clone(), scores.clone()]);
+        let roots = vec![ShaSMA::new(ids).root(), ShaSMA::new(scores).root()];
+        assert_eq!(trees.iter().map(|t| t.root()).collect::<Vec<_>>(), roots);
+        assert_eq!(combined, ShaSMA::new(roots).root());
+    }
+
+    #[test]
+    fn io_errors_name_the_file() {
+        let path = std::env::temp_dir()
+            .join(format!("sma_missing_{}", std::process::id()))
+            .join("tree.bin");
+        let err = ShaSMA::<u64>::load_from_file(&path).unwrap_err();
+        assert!(matches!(&err, MerkleError::Io { path: p, .. } if *p == path));
+        assert!(err.to_string().contains("tree.bin"));
+
+        let sm = ShaSMA::new(vec![1u64, 2]);
+        let proof = sm.prove_index(0).unwrap();
+        assert!(matches!(
+            proof.save_to_file(&path),
+            Err(MerkleError::Io { path: p, .. }) if p == path
+        ));
+    }
+
+    #[test]
+    fn files_carry_a_format_version() {
+        let sm = ShaSMA::<u64>::new((0..5).collect());
+        let path = std::env::temp_dir().join(format!("sma_versioned_{}.bin", std::process::id()));
+        sm.save_to_file(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes[..4], FILE_MAGIC);
+        assert_eq!(bytes[4], FORMAT_VERSION);
+
+        let mut bumped = bytes.clone();
+        bumped[4] = FORMAT_VERSION + 1;
+        std::fs::write(&path, &bumped).unwrap();
+        assert!(matches!(
+            ShaSMA::<u64>::load_from_file(&path),
+            Err(MerkleError::VersionMismatch { found, expected: FORMAT_VERSION }) if found == FORMAT_VERSION + 1
+        ));
+
+        // Pre-versioning files were bare bincode.
+        std::fs::write(&path, bincode::serialize(&sm).unwrap()).unwrap();
+        assert!(matches!(
+            ShaSMA::<u64>::load_from_file(&path),
+            Err(MerkleError::VersionMismatch { found: 0, .. })
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn proof_persistence_roundtrip() {
+        let arr: Vec<u64> = (0..25).collect();
+        let sm = ShaSMA::new(arr.clone());
+        let root_before = sm.root();
+
+        let path = std::env::temp_dir().join("sma.bin");
+        
+
+        // Proof from loaded still verifies
+        let proof = sm.prove_index(13).unwrap();
+
+        proof.save_to_file(&path).unwrap();
+        let loaded_proof = MerkleProof::<Sha256Hasher>::load_from_file(&path).unwrap();
+        assert_eq!(loaded_proof.get_merkle_root(), root_before);
+        assert!(verify_value_with_proof(&arr[13], &proof));
+        assert!(verify_value_with_proof(&arr[13], &loaded_proof));
+    }
+
+    #[test]
+    fn serialized_len_matches_bincode() {
+        let plain = ShaSMA::new((0..25u64).collect());
+        let salted = ShaSMA::new_salted((0..25u64).collect(), [3u8; 32]);
+        for sm in [&plain, &salted] {
+            let proof = sm.prove_index(13).unwrap();
+            assert_eq!(
+                proof.serialized_len(),
+                bincode::serialize(&proof).unwrap().len()
+            );
+            let multi = sm.prove_indices(&[1, 4, 5, 12, 24]).unwrap();
+            assert_eq!(
+                multi.serialized_len(),
+                bincode::serialize(&multi).unwrap().len()
+            );
+        }
+    }
+
+    #[test]
+    fn min_depth_rejects_degenerate_proof() {
+        let arr: Vec<u64> = (0..8).collect();
+        let sm = ShaSMA::new(arr);
+        let proof = sm.prove_index(5).unwrap();
+        assert!(proof.verify_min_depth(3));
+        assert!(!proof.verify_min_depth(4));
+
+        // `{siblings: [], leaf: X, root: X}` passes plain `verify`.
+        let forged = MerkleProof::<Sha256Hasher> {
+            index: 0,
+            tree_len: 1,
+            siblings: vec![],
+            root: proof.leaf,
+            leaf: proof.leaf,
+            salt: None,
+            position_bound: false,
+        };
+        assert!(forged.verify());
+        assert!(!forged.verify_min_depth(1));
+    }
+
+    #[test]
+    fn recompute_root_reports_mismatch() {
+        let arr: Vec<u64> = (0..6).collect();
+        let sm = ShaSMA::new(arr);
+        let mut proof = sm.prove_index(4).unwrap();
+        assert_eq!(proof.recompute_root(), sm.root());
+
+        proof.leaf = Sha256Hasher::leaf(&99u64);
+        assert!(!proof.verify());
+        assert_ne!(proof.recompute_root(), proof.root);
+    }
+
+    #[test]
+    fn equivalent_proofs_compare_by_recomputed_path() {
+        let arr: Vec<u64> = (0..5).collect();
+        let sm = ShaSMA::new(arr);
+        let a = sm.prove_index(4).unwrap();
+        let b = sm.proof_context().prove(4).unwrap();
+        assert!(a.is_equivalent(&b));
+
+        assert!(!a.is_equivalent(&sm.prove_index(3).unwrap()));
+
+        let mut wrong_path = a.clone();
+        wrong_path.siblings[0].0 = Sha256Hasher::leaf(&7u64);
+        assert!(!a.is_equivalent(&wrong_path));
+    }
+
+    #[test]
+    fn verify_rejects_index_too_large_for_depth() {
+        let arr: Vec<u64> = (0..8).collect();
+        let sm = ShaSMA::new(arr);
+        let mut proof = sm.prove_index(7).unwrap();
+        assert_eq!(proof.siblings.len(), 3);
+        assert!(proof.verify());
+
+        proof.index = 8;
+        assert!(!proof.verify());
+        proof.index = 1_000_000;
+        assert!(!proof.verify());
+
+        // A single-leaf proof can only be for index 0.
+        let single = ShaSMA::new(vec![42u64]).prove_index(0).unwrap();
+        assert!(single.verify());
+        let mut shifted = single.clone();
+        shifted.index = 1;
+        assert!(!shifted.verify());
+    }
+
+    #[test]
+    fn verify_rejects_proof_for_other_tree_len() {
+        let arr: Vec<u64> = (0..8).collect();
+        let sm = ShaSMA::new(arr);
+        let proof = sm.prove_index(2).unwrap();
+        assert_eq!(proof.tree_len, 8);
+
+        // Same root and path, but claimed to come from a tree of a different size.
+        for tree_len in [0, 2, 3, 4, 9, 16] {
+            let mut p = proof.clone();
+            p.tree_len = tree_len;
+            assert!(!p.verify(), "tree_len = {tree_len}");
+        }
+        // Any size with depth 3 is shape-consistent for index 2.
+        let mut p = proof.clone();
+        p.tree_len = 5;
+        assert!(p.verify());
+
+        let mut shallow = proof.clone();
+        shallow.siblings.pop();
+        assert!(!shallow.verify());
+
+        // `verify_fast` skips the shape checks and only looks at the hashes.
+        let mut unchecked = proof.clone();
+        unchecked.index = 1_000;
+        assert!(!unchecked.verify());
+        assert!(unchecked.verify_fast());
+        assert!(!shallow.verify_fast());
+    }
+
+    #[test]
+    fn proofs_dedup_in_hash_set() {
+        use std::collections::HashSet;
+
+        let arr: Vec<u64> = (0..6).collect();
+        let sm = ShaSMA::new(arr);
+        let mut set = HashSet::new();
+        for i in [0, 3, 3, 5, 0] {
+            set.insert(sm.prove_index(i).unwrap());
+        }
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&sm.proof_context().prove(5).unwrap()));
+
+        // Same (index, leaf, root) but a different path: still a distinct member.
+        let mut odd = sm.prove_index(3).unwrap();
+        odd.siblings[0].0 = Sha256Hasher::leaf(&9u64);
+        assert!(set.insert(odd));
+    }
+
+    #[test]
+    fn extend_with_chains_into_forest_root() {
+        let trees: Vec<ShaSMA<u64>> = (0..3)
+            .map(|t| ShaSMA::new((0..5).map(|x| 10 * t + x).collect()))
+            .collect();
+        let roots: Vec<Hash32> = trees.iter().map(|t| t.root()).collect();
+        // Forest leaves are the tree roots themselves, not `leaf(root)`.
+        let forest =
+            StaticMerkleArray::<u64, Sha256Hasher>::try_from_leaves(vec![0; 3], roots).unwrap();
+
+        for (t, tree) in trees.iter().enumerate() {
+            let upper = forest.prove_index(t).unwrap();
+            for i in 0..tree.len() {
+                let chained = tree.prove_index(i).unwrap().extend_with(&upper).unwrap();
+                assert_eq!(chained.index, (t << 3) | i);
+                assert_eq!(chained.root, forest.root());
+                assert!(chained.verify(), "tree {t} leaf {i}");
+            }
+        }
+
+        let lower = trees[0].prove_index(0).unwrap();
+        assert!(matches!(
+            lower.extend_with(&forest.prove_index(1).unwrap()),
+            Err(MerkleError::RootMismatch)
+        ));
+    }
+
+    #[test]
+    fn padded_levels_match_duplicated_siblings() {
+        let arr: Vec<u64> = (0..11).collect();
+        let sm = ShaSMA::new(arr);
+        // Widths per level: 11, 6, 3, 2 -> leaf 10 pads at 0, node 2 of level 2 pads.
+        for i in 0..sm.len() {
+            let p = sm.prove_index(i).unwrap();
+            let own: Vec<usize> = (0..p.siblings.len())
+                .filter(|&l| p.siblings[l].0 == sm.levels[l][i >> l])
+                .collect();
+            assert_eq!(p.padded_levels(), own, "index {i}");
+        }
+        assert_eq!(sm.prove_index(10).unwrap().padded_levels(), vec![0, 2]);
+        assert_eq!(sm.prove_index(9).unwrap().padded_levels(), vec![2]);
+        assert!(!sm.prove_index(3).unwrap().is_padded_edge());
+        assert!(!ShaSMA::new(vec![1u64, 2, 3, 4])
+            .prove_index(3)
+            .unwrap()
+            .is_padded_edge());
+    }
+
+    #[test]
+    fn proof_builder_validates_siblings() {
+        let arr: Vec<u64> = (0..11).collect();
+        let sm = ShaSMA::new(arr);
+        let p = sm.prove_index(10).unwrap();
+
+        let mut b = MerkleProofBuilder::<Sha256Hasher>::new(p.index, p.tree_len, p.leaf, p.root);
+        for (d, side) in &p.siblings {
+            b.push_sibling(*d, *side).unwrap();
+        }
+        let rebuilt = b.build().unwrap();
+        assert!(rebuilt.verify());
+        assert!(rebuilt.is_equivalent(&p));
+
+        // index 10 = 0b1010: sibling 1 must be on the left.
+        let mut b = MerkleProofBuilder::<Sha256Hasher>::new(p.index, p.tree_len, p.leaf, p.root);
+        b.push_sibling(p.siblings[0].0, Side::Right).unwrap();
+        assert!(matches!(
+            b.push_sibling(p.siblings[1].0, Side::Right),
+            Err(MerkleError::SideMismatch { position: 1 })
+        ));
+
+        // Too few siblings for index 10.
+        let mut b = MerkleProofBuilder::<Sha256Hasher>::new(p.index, p.tree_len, p.leaf, p.root);
+        b.push_sibling(p.siblings[0].0, Side::Right).unwrap();
+        assert!(matches!(
+            b.build(),
+            Err(MerkleError::DepthMismatch {
+                index: 10,
+                depth: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn byte_proof_verifies_with_runtime_hash() {
+        let arr: Vec<u64> = (0..9).collect();
+        let sm = ShaSMA::new(arr);
+        let proof = sm.prove_index(8).unwrap();
+        let bp = proof.into_byte_proof();
+        assert_eq!(bp.siblings.len(), proof.siblings.len());
+
+        let node = |l: &[u8], r: &[u8]| {
+            let mut buf = vec![NODE_TAG];
+            buf.extend_from_slice(l);
+            buf.extend_from_slice(r);
+            sha256(&buf).0.to_vec()
+        };
+        assert!(bp.verify_with(node));
+
+        let mut bad = bp.clone();
+        bad.leaf[0] ^= 1;
+        assert!(!bad.verify_with(node));
+    }
+
+    #[test]
+    fn node_into_matches_node() {
+        let a = Sha256Hasher::leaf(&1u64);
+        let b = Sha256Hasher::leaf(&2u64);
+        let mut out = Hash32::default();
+        Sha256Hasher::node_into(&mut out, &a, &b);
+        assert_eq!(out, Sha256Hasher::node(&a, &b));
+    }
+
+    #[test]
+    fn root_diff_byte_finds_first_mismatch() {
+        let a = [1u8; 32];
+        let mut b = a;
+        assert_eq!(root_diff_byte(&a, &b), None);
+        b[31] = 0;
+        b[7] = 0;
+        assert_eq!(root_diff_byte(&a, &b), Some(7));
+    }
+
+    #[test]
+    fn zero_subtree_roots_match_zero_tree() {
+        let zeros = zero_subtree_roots::<Sha256Hasher>(3);
+        assert_eq!(zeros.len(), 4);
+        assert_eq!(zeros[0], Hash32([0u8; 32]));
+        assert_eq!(zeros[1], Sha256Hasher::node(&zeros[0], &zeros[0]));
+        assert_eq!(zeros[3], Sha256Hasher::node(&zeros[2], &zeros[2]));
+    }
+
+    #[test]
+    fn multiproof_normalizes_indices() {
+        let arr: Vec<u64> = (0..13).collect();
+        let sm = ShaSMA::new(arr);
+
+        let canonical = sm.prove_indices(&[1, 4, 5, 12]).unwrap();
+        assert!(canonical.verify());
+        assert_eq!(canonical.indices, vec![1, 4, 5, 12]);
+
+        let messy = sm.prove_indices(&[12, 5, 1, 4, 5, 12]).unwrap();
+        assert_eq!(messy.indices, canonical.indices);
+        assert_eq!(messy.leaves, canonical.leaves);
+        assert_eq!(messy.siblings, canonical.siblings);
+
+        assert!(matches!(
+            sm.prove_indices(&[0, 13]),
+            Err(MerkleError::IndexOob)
+        ));
+        assert!(matches!(
+            sm.prove_indices(&[]),
+            Err(MerkleError::EmptyInput)
+        ));
+
+        let mut tampered = canonical.clone();
+        tampered.leaves.swap(0, 1);
+        assert!(!tampered.verify());
+    }
+
+    #[test]
+    fn from_lines_file_handles_trailing_newline() {
+        let path = std::env::temp_dir().join(format!("sma_lines_{}.txt", std::process::id()));
+
+        std::fs::write(&path, "alpha\nbeta\r\n\ngamma\n").unwrap();
+        let sm = ShaSMA::<String>::from_lines_file(&path).unwrap();
+        assert_eq!(sm.len(), 4);
+        let p = sm.prove_index(1).unwrap();
+        assert!(verify_value_with_proof(&"beta".to_string(), &p));
+
+        std::fs::write(&path, "").unwrap();
+        assert!(matches!(
+            ShaSMA::<String>::from_lines_file(&path),
+            Err(MerkleError::EmptyInput)
+        ));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn prove_all_matches_prove_index() {
+        let arr: Vec<u64> = (0..7).collect();
+        let sm = ShaSMA::new(arr.clone());
+        let all = sm.prove_all();
+        assert_eq!(all.len(), arr.len());
+        for (i, p) in all.iter().enumerate() {
+            assert_eq!(p.siblings, sm.prove_index(i).unwrap().siblings);
+            assert!(verify_value_with_proof(&arr[i], p));
+        }
+        assert!(matches!(
+            sm.proof_context().prove(7),
+            Err(MerkleError::IndexOob)
+        ));
+    }
+
+    #[test]
+    fn update_leaf_matches_rebuild() {
+        let mut arr: Vec<u64> = vec![5, 1, 5, 3, 4, 5, 6, 7, 8, 9, 10];
+        let mut sm = ShaSMA::new(arr.clone());
+        for (index, value) in [(10, 100), (0, 7), (4, 5), (10, 5)] {
+            sm.update_leaf(index, value).unwrap();
+            arr[index] = value;
+            let fresh = ShaSMA::new(arr.clone());
+            assert_eq!(sm.root(), fresh.root());
+            assert_eq!(sm.levels, fresh.levels);
+        }
+        assert_eq!(sm.positions_of(&5), vec![2, 4, 5, 10]);
+        assert_eq!(sm.positions_of(&7), vec![0, 7]);
+        assert!(sm.positions_of(&100).is_empty());
+        assert!(verify_value_with_proof(&5u64, &sm.prove_index(10).unwrap()));
+        assert!(matches!(sm.update_leaf(11, 0), Err(MerkleError::IndexOob)));
+    }
+
+    #[test]
+    fn rebuild_index_map_repairs_a_stale_map() {
+        let arr: Vec<u64> = vec![5, 1, 5, 3, 4, 5, 6];
+        let mut sm = ShaSMA::new(arr);
+        let five = sm.levels[0][0];
+        sm.index_map.remove(&five);
+        sm.index_map.insert(sm.levels[0][1], vec![0, 6]);
+        assert!(sm.positions_of(&5).is_empty());
+
+        sm.rebuild_index_map();
+        assert_eq!(sm.positions_of(&5), vec![0, 2, 5]);
+        assert_eq!(sm.positions_of(&1), vec![1]);
+        assert_eq!(sm.positions_of(&6), vec![6]);
+        assert!(verify_value_with_proof(
+            &5u64,
+            &sm.prove_item(&5, Some(2)).unwrap()
+        ));
+    }
+
+    #[test]
+    fn value_multi_proof_covers_every_occurrence() {
+        let arr: Vec<u64> = vec![4, 1, 4, 2, 3, 4, 5, 6, 4];
+        let sm = ShaSMA::new(arr);
+        let mp = sm.prove_value_multi(&4).unwrap();
+        assert_eq!(mp.indices, vec![0, 2, 5, 8]);
+        assert!(verify_value_multi(&4u64, &mp));
+        assert!(!verify_value_multi(&1u64, &mp));
+        let separate: usize = mp
+            .indices
+            .iter()
+            .map(|&i| sm.prove_index(i).unwrap().serialized_len())
+            .sum();
+        assert!(mp.serialized_len() < separate);
+        assert!(matches!(
+            sm.prove_value_multi(&7),
+            Err(MerkleError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn rebuild_with_replacements_reports_changed_leaves() {
+        let arr: Vec<u64> = (0..10).collect();
+        let sm = ShaSMA::new(arr);
+
+        let (rebuilt, changed) = sm
+            .rebuild_with_replacements(&[(7, 70), (2, 2), (4, 40), (7, 71)])
+            .unwrap();
+        assert_eq!(changed, vec![4, 7]);
+        assert_ne!(rebuilt.root(), sm.root());
+        let p7 = rebuilt.prove_index(7).unwrap();
+        assert!(verify_value_with_proof(&71u64, &p7));
+        assert_eq!(rebuilt.positions_of(&7), Vec::<usize>::new());
+
+        let (same, none) = sm.rebuild_with_replacements(&[(3, 3)]).unwrap();
+        assert!(none.is_empty());
+        assert_eq!(same.root(), sm.root());
+
+        assert!(matches!(
+            sm.rebuild_with_replacements(&[(10, 0)]),
+            Err(MerkleError::IndexOob)
+        ));
+    }
+
+    #[test]
+    fn try_new_matches_new() {
+        let arr: Vec<u64> = (0..19).collect();
+        let a = ShaSMA::try_new(arr.clone()).unwrap();
+        assert_eq!(a.root(), ShaSMA::new(arr).root());
+        assert!(matches!(
+            ShaSMA::<u64>::try_new(Vec::new()),
+            Err(MerkleError::EmptyInput)
+        ));
+    }
+
+    /// A hasher whose backend refuses to hash the leaf `13u64`.
+    #[derive(Debug, Clone, Copy, Default)]
+    struct FlakyHasher;
+
+    impl MerkleHasher for FlakyHasher {
+        type Digest = Hash32;
+
+        fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+            Self::try_leaf(item).expect("backend failure")
+        }
+
+        fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+            Sha256Hasher::node(left, right)
+        }
+
+        fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+            if bincode::serialize(item)? == bincode::serialize(&13u64)? {
+                return Err("backend unavailable".into());
+            }
+            Ok(Sha256Hasher::leaf(item))
+        }
+    }
+
+    #[test]
+    fn try_new_propagates_hasher_errors() {
+        let ok = StaticMerkleArray::<u64, FlakyHasher>::try_new((0..13).collect()).unwrap();
+        assert_eq!(ok.root(), ShaSMA::new((0..13).collect::<Vec<u64>>()).root());
+
+        let err = StaticMerkleArray::<u64, FlakyHasher>::try_new((0..14).collect()).unwrap_err();
+        assert!(matches!(err, MerkleError::Hasher(_)));
+        assert_eq!(err.to_string(), "hash backend failed: backend unavailable");
+
+        assert!(matches!(
+            ok.rebuild_with_replacements(&[(3, 13)]),
+            Err(MerkleError::Hasher(_))
+        ));
+    }
+
+    #[test]
+    fn explain_distinguishes_value_from_path() {
+        let sm = ShaSMA::<u64>::new((0..6).collect());
+        let p = sm.prove_index(4).unwrap();
+        assert_eq!(verify_value_with_proof_explain(&4u64, &p), Ok(()));
+        assert!(matches!(
+            verify_value_with_proof_explain(&5u64, &p),
+            Err(VerifyError::LeafMismatch { expected, .. }) if expected == p.leaf
+        ));
+
+        let mut bad_path = p.clone();
+        bad_path.siblings[1].0 = bad_path.leaf;
+        assert!(matches!(
+            verify_value_with_proof_explain(&4u64, &bad_path),
+            Err(VerifyError::RootMismatch { expected, .. }) if expected == sm.root()
+        ));
+        let mut bad_shape = p;
+        bad_shape.siblings.pop();
+        assert!(matches!(
+            verify_value_with_proof_explain(&4u64, &bad_shape),
+            Err(VerifyError::Shape { depth: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn authenticate_reports_each_failure() {
+        let sm = ShaSMA::<u64>::new((0..6).collect());
+        let other = ShaSMA::<u64>::new((1..7).collect());
+        let root = sm.root();
+        let p = sm.prove_index(2).unwrap();
+        assert_eq!(authenticate(&2u64, 2, &p, &root), Ok(()));
+        assert!(matches!(
+            authenticate(&2u64, 3, &p, &root),
+            Err(AuthError::IndexMismatch {
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            authenticate(&2u64, 2, &p, &other.root()),
+            Err(AuthError::UntrustedRoot { .. })
+        ));
+        assert!(matches!(
+            authenticate(&9u64, 2, &p, &root),
+            Err(AuthError::Proof(VerifyError::LeafMismatch { .. }))
+        ));
+    }
+
+    #[test]
+    fn commit_and_prove_one_shot() {
+        let items: Vec<u64> = (0..7).collect();
+        let (root, proof) = commit_and_prove::<u64, Sha256Hasher>(items.clone(), 4).unwrap();
+        let sm = ShaSMA::new(items.clone());
+        assert_eq!(root, sm.root());
+        assert_eq!(proof, sm.prove_index(4).unwrap());
+        assert!(authenticate_at(&proof, &4u64, 4, &root));
+
+        assert!(matches!(
+            commit_and_prove::<u64, Sha256Hasher>(items, 7),
+            Err(MerkleError::IndexOob)
+        ));
+        assert!(matches!(
+            commit_and_prove::<u64, Sha256Hasher>(vec![], 0),
+            Err(MerkleError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn authenticate_at_binds_item_index_and_root() {
+        let sm = ShaSMA::<u64>::new(vec![5, 7, 5, 9]);
+        let root = sm.root();
+        let p = sm.prove_index(2).unwrap();
+        assert!(authenticate_at(&p, &5u64, 2, &root));
+        // Same value at index 0: the leaf matches, the position doesn't.
+        assert!(!authenticate_at(&p, &5u64, 0, &root));
+        assert!(!authenticate_at(&p, &7u64, 2, &root));
+        assert!(!authenticate_at(
+            &p,
+            &5u64,
+            2,
+            &sm.prove_index(0).unwrap().leaf
+        ));
+
+        let mut forged = p.clone();
+        forged.siblings[0].0 = forged.siblings[1].0;
+        assert!(!authenticate_at(&forged, &5u64, 2, &root));
+    }
+
+    #[test]
+    fn verify_from_loose_parts() {
+        let arr: Vec<u64> = (0..9).collect();
+        let sm = ShaSMA::new(arr);
+        let p = sm.prove_index(6).unwrap();
+        let root = sm.root();
+        assert!(verify_from_parts::<u64, Sha256Hasher>(
+            &6,
+            p.index,
+            p.tree_len,
+            &p.siblings,
+            &root
+        ));
+        assert!(!verify_from_parts::<u64, Sha256Hasher>(
+            &5,
+            p.index,
+            p.tree_len,
+            &p.siblings,
+            &root
+        ));
+        assert!(!verify_from_parts::<u64, Sha256Hasher>(
+            &6,
+            1 << 10,
+            p.tree_len,
+            &p.siblings,
+            &root
+        ));
+        // The path is for index 6; claiming another index in range fails.
+        for wrong in [4, 7, 2] {
+            assert!(!verify_from_parts::<u64, Sha256Hasher>(
+                &6,
+                wrong,
+                p.tree_len,
+                &p.siblings,
+                &root
+            ));
+        }
+
+        // The bare path check borrows the siblings straight from a larger buffer.
+        let buffer: Vec<_> = [p.siblings.clone(), p.siblings.clone()].concat();
+        let sibs = &buffer[p.siblings.len()..];
+        assert!(verify_with_siblings::<Sha256Hasher>(&p.leaf, sibs, &root));
+        assert!(!verify_with_siblings::<Sha256Hasher>(
+            &p.leaf,
+            &sibs[1..],
+            &root
+        ));
+    }
+
+    #[test]
+    fn expectations_report_first_failure() {
+        let arr: Vec<u64> = (0..10).collect();
+        let sm = ShaSMA::new(arr);
+        let root = sm.root();
+        let mut expected: Vec<(usize, u64, MerkleProof<Sha256Hasher>)> = [2usize, 5, 9]
+            .iter()
+            .map(|&i| (i, i as u64, sm.prove_index(i).unwrap()))
+            .collect();
+        assert_eq!(verify_expectations(&root, &expected), Ok(()));
+
+        expected[1].1 = 50;
+        assert_eq!(verify_expectations(&root, &expected), Err(5));
+
+        expected[1].1 = 5;
+        expected[2].0 = 8;
+        assert_eq!(verify_expectations(&root, &expected), Err(8));
+
+        let other = ShaSMA::new(vec![1u64, 2, 3]).root();
+        assert_eq!(verify_expectations(&other, &expected), Err(2));
+    }
+
+    #[test]
+    fn new_with_limit_rejects_oversized_input() {
+        let arr: Vec<u64> = (0..5).collect();
+        assert!(ShaSMA::new_with_limit(arr.clone(), 5).is_ok());
+        assert!(matches!(
+            ShaSMA::new_with_limit(arr, 4),
+            Err(MerkleError::TooLarge { len: 5, max: 4 })
+        ));
+    }
+
+    #[test]
+    fn rank_over_sorted_build() {
+        let sm = ShaSMA::new_sorted(vec![30u64, 10, 20, 20, 50]);
+        assert_eq!(sm.positions_of(&10), vec![0]);
+        assert_eq!(sm.rank(&5), 0);
+        assert_eq!(sm.rank(&10), 1);
+        assert_eq!(sm.rank(&20), 3);
+        assert_eq!(sm.rank(&49), 4);
+        assert_eq!(sm.rank(&99), 5);
+    }
+
+    /// `Sha256Hasher` with a root finalization that tags in the leaf count.
+    #[derive(Debug, Clone, Copy, Default)]
+    struct LenBoundHasher;
+
+    impl MerkleHasher for LenBoundHasher {
+        type Digest = Hash32;
+
+        fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+            Sha256Hasher::leaf(item)
+        }
+
+        fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+            Sha256Hasher::node(left, right)
+        }
+
+        fn finalize_root(top: &Self::Digest, n_leaves: usize) -> Self::Digest {
+            let mut buf = vec![0x02];
+            buf.extend_from_slice(&top.0);
+            buf.extend_from_slice(&(n_leaves as u64).to_be_bytes());
+            sha256(&buf)
+        }
+    }
+
+    #[test]
+    fn finalized_root_binds_leaf_count() {
+        let arr: Vec<u64> = (0..8).collect();
+        let big = StaticMerkleArray::<u64, LenBoundHasher>::new(arr.clone());
+        let small = StaticMerkleArray::<u64, LenBoundHasher>::new(arr[..4].to_vec());
+
+        // Unfinalized, the small tree's root is just the big tree's left subtree.
+        assert_eq!(small.levels.last().unwrap()[0], big.levels[2][0]);
+        assert_ne!(small.root(), big.levels[2][0]);
+        assert_ne!(big.root(), big.levels.last().unwrap()[0]);
+
+        for (i, v) in arr.iter().enumerate() {
+            let p = big.prove_index(i).unwrap();
+            assert_eq!(p.tree_len, 8);
+            assert!(verify_value_with_proof(v, &p));
+        }
+        let mut p = big.prove_index(3).unwrap();
+        p.tree_len = 7;
+        assert!(!p.verify());
+
+        let multi = big.prove_indices(&[1, 6]).unwrap();
+        assert!(multi.verify());
+        let mut wrong_len = multi.clone();
+        wrong_len.tree_len = 9;
+        assert!(!wrong_len.verify());
+    }
+
+    #[test]
+    fn byte_range_proofs_cover_chunks() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+        let sm = ShaSMA::<Vec<u8>>::from_chunks(&data, 4096);
+        assert_eq!(sm.len(), 3);
+
+        let p = sm.prove_byte_range(4000, 4200).unwrap();
+        assert_eq!(p.indices, vec![0, 1]);
+        assert!(p.verify());
+        for (k, &i) in p.indices.iter().enumerate() {
+            let chunk = data[i * 4096..((i + 1) * 4096).min(data.len())].to_vec();
+            assert_eq!(p.leaves[k], Sha256Hasher::leaf(&chunk));
+        }
+
+        let tail = sm.prove_byte_range(9000, 10_000).unwrap();
+        assert_eq!(tail.indices, vec![2]);
+        assert!(matches!(
+            sm.prove_byte_range(9000, 10_001),
+            Err(MerkleError::IndexOob)
+        ));
+        assert!(matches!(
+            sm.prove_byte_range(5, 5),
+            Err(MerkleError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn fixed_width_commits_empty_slots() {
+        let slots = vec![Some(10u64), None, Some(30)];
+        let sm = StaticMerkleArray::<Option<u64>, Sha256Hasher>::new_fixed_width(slots, 5).unwrap();
+        assert_eq!(sm.len(), 5);
+
+        let p0 = sm.prove_index(0).unwrap();
+        assert!(verify_value_with_proof(&Some(10u64), &p0));
+        for i in [1, 3, 4] {
+            assert_eq!(sm.prove_index(i).unwrap().leaf, Sha256Hasher::empty_leaf());
+        }
+        assert_ne!(
+            Sha256Hasher::empty_leaf(),
+            Sha256Hasher::leaf(&Vec::<u8>::new())
+        );
+        assert_eq!(sm.positions_of(&Some(30)), vec![2]);
+        assert_eq!(sm.positions_of(&None), vec![1, 3, 4]);
+        assert!(sm.verify_self_proofs());
+
+        // Filling and emptying slots keeps the leaves those of a fresh build.
+        let mut edited = sm.clone();
+        edited.update_leaf(1, Some(20)).unwrap();
+        edited.update_leaf(2, None).unwrap();
+        assert!(edited.verify_self_proofs());
+        let fresh = StaticMerkleArray::<Option<u64>, Sha256Hasher>::new_fixed_width(
+            vec![Some(10), Some(20)],
+            5,
+        )
+        .unwrap();
+        assert_eq!(edited.root(), fresh.root());
+        assert_eq!(edited.positions_of(&None), vec![2, 3, 4]);
+        let (rebuilt, changed) = sm
+            .rebuild_with_replacements(&[(1, Some(20)), (2, None)])
+            .unwrap();
+        assert_eq!(rebuilt.root(), fresh.root());
+        assert_eq!(changed, vec![1, 2]);
+        assert!(edited.pad_to_depth(3).unwrap().verify_self_proofs());
+
+        assert!(matches!(
+            StaticMerkleArray::<Option<u64>, Sha256Hasher>::new_fixed_width(vec![None; 3], 2),
+            Err(MerkleError::TooLarge { len: 3, max: 2 })
+        ));
+        assert!(matches!(
+            StaticMerkleArray::<Option<u64>, Sha256Hasher>::new_fixed_width(vec![], 0),
+            Err(MerkleError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn salted_leaves_hide_small_domains() {
+        let flags = vec![true, false, false, true, true];
+        let salt = [7u8; 32];
+        let sm = StaticMerkleArray::<bool, Sha256Hasher>::new_salted(flags.clone(), salt);
+        let plain = StaticMerkleArray::<bool, Sha256Hasher>::new(flags.clone());
+        assert_ne!(sm.root(), plain.root());
+        assert_eq!(sm.salt(), Some(&salt));
+
+        // Enumerating the domain without the salt matches no leaf.
+        for guess in [true, false] {
+            assert!(sm
+                .positions_of_digest(&Sha256Hasher::leaf(&guess))
+                .is_empty());
+        }
+        assert_eq!(sm.positions_of(&true), vec![0, 3, 4]);
+
+        let proof = sm.prove_item(&false, Some(1)).unwrap();
+        assert_eq!(proof.salt, Some(salt));
+        assert_eq!(
+            proof.serialized_len(),
+            bincode::serialize(&proof).unwrap().len()
+        );
+        assert!(verify_value_with_proof(&false, &proof));
+        assert!(!verify_value_with_proof(&true, &proof));
+        let mut unsalted = proof.clone();
+        unsalted.salt = None;
+        assert!(!verify_value_with_proof(&false, &unsalted));
+
+        let multi = sm.prove_value_multi(&true).unwrap();
+        assert_eq!(
+            multi.serialized_len(),
+            bincode::serialize(&multi).unwrap().len()
+        );
+        assert!(verify_value_multi(&true, &multi));
+
+        let mut updated = sm.clone();
+        updated.update_leaf(1, true).unwrap();
+        assert!(updated.verify_self_proofs());
+        let (rebuilt, _) = sm.rebuild_with_replacements(&[(1, true)]).unwrap();
+        assert_eq!(rebuilt.root(), updated.root());
+    }
+
+    #[test]
+    fn subset_root_links_to_full_root() {
+        let items: Vec<u64> = (0..10).map(|i| i * 3).collect();
+        let sm = ShaSMA::new(items.clone());
+        let (root, proofs) = sm.subset_root(&[7, 2, 5]).unwrap();
+        let kept = ShaSMA::new(vec![items[7], items[2], items[5]]);
+        assert_eq!(root, kept.root());
+        assert!(verify_subset_root(&root, &sm.root(), &proofs));
+        assert!(verify_value_with_proof(&items[2], &proofs[1]));
+
+        assert!(!verify_subset_root(&sm.root(), &sm.root(), &proofs));
+        assert!(!verify_subset_root(&root, &kept.root(), &proofs));
+        let mut reordered = proofs.clone();
+        reordered.swap(0, 1);
+        assert!(!verify_subset_root(&root, &sm.root(), &reordered));
+
+        assert!(matches!(sm.subset_root(&[]), Err(MerkleError::EmptyInput)));
+        assert!(matches!(sm.subset_root(&[10]), Err(MerkleError::IndexOob)));
+    }
+
+    #[test]
+    fn proof_positions_encode_as_u64() {
+        let sm = ShaSMA::new((0..300u64).collect());
+        let proof = sm.prove_index(258).unwrap();
+        let bytes = bincode::serialize(&proof).unwrap();
+        assert_eq!(bytes[..8], 258u64.to_le_bytes());
+        assert_eq!(bytes[8..16], 300u64.to_le_bytes());
+        let back: MerkleProof<Sha256Hasher> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back, proof);
+
+        let multi = sm.prove_indices(&[3, 299]).unwrap();
+        let bytes = bincode::serialize(&multi).unwrap();
+        assert_eq!(bytes[8..16], 3u64.to_le_bytes());
+        assert_eq!(multi.serialized_len(), bytes.len());
+        let back: MultiProof<Sha256Hasher> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back.indices, multi.indices);
+        assert!(back.verify());
+
+        let bytes = bincode::serialize(&proof.into_byte_proof()).unwrap();
+        assert_eq!(
+            bytes[..16],
+            [258u64.to_le_bytes(), 300u64.to_le_bytes()].concat()
+        );
+
+        let subtree = sm.prove_subtree_root(2, 64).unwrap();
+        let bytes = bincode::serialize(&subtree).unwrap();
+        assert_eq!(bytes[..8], 2u64.to_le_bytes());
+        assert_eq!(bytes[8..16], 64u64.to_le_bytes());
+        let back: SubtreeProof<Sha256Hasher> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!((back.node_index, back.tree_len), (64, 300));
+        assert!(back.verify());
+    }
+
+    #[test]
+    fn sanity_check_rejects_degenerate_hashers() {
+        #[derive(Clone, Copy, Debug, Default)]
+        struct Constant;
+        impl MerkleHasher for Constant {
+            type Digest = Hash32;
+            fn leaf<T: Serialize>(_: &T) -> Hash32 {
+                Hash32([7; 32])
+            }
+            fn node(_: &Hash32, _: &Hash32) -> Hash32 {
+                Hash32([7; 32])
+            }
+        }
+
+        #[derive(Clone, Copy, Debug, Default)]
+        struct Xor;
+        impl MerkleHasher for Xor {
+            type Digest = Hash32;
+            fn leaf<T: Serialize>(item: &T) -> Hash32 {
+                Sha256Hasher::leaf(item)
+            }
+            fn node(l: &Hash32, r: &Hash32) -> Hash32 {
+                Hash32(std::array::from_fn(|i| l.0[i] ^ r.0[i]))
+            }
+        }
+
+        assert!(ShaSMA::<u64>::sanity_check_hasher().is_ok());
+        assert!(ShaSMA::try_new_checked(vec![1u64, 2]).is_ok());
+        assert!(matches!(
+            StaticMerkleArray::<u64, Constant>::try_new_checked(vec![1, 2]),
+            Err(MerkleError::DegenerateHasher {
+                check: "distinct leaves hash equal"
+            })
+        ));
+        assert!(matches!(
+            StaticMerkleArray::<u64, Xor>::sanity_check_hasher(),
+            Err(MerkleError::DegenerateHasher {
+                check: "node ignores child order"
+            })
+        ));
+    }
+
+    #[test]
+    fn common_ancestor_level_is_where_paths_share_siblings() {
+        let sm = ShaSMA::new((0..13u64).collect());
+        assert_eq!(sm.common_ancestor_level(6, 6).unwrap(), 0);
+        assert_eq!(sm.common_ancestor_level(4, 5).unwrap(), 1);
+        assert_eq!(sm.common_ancestor_level(3, 4).unwrap(), 3);
+        assert_eq!(sm.common_ancestor_level(12, 0).unwrap(), 4);
+        for (i, j) in [(0, 1), (2, 7), (8, 12), (5, 5)] {
+            let level = sm.common_ancestor_level(i, j).unwrap();
+            let (a, b) = (sm.prove_index(i).unwrap(), sm.prove_index(j).unwrap());
+            assert_eq!(a.siblings[level..], b.siblings[level..]);
+        }
+        assert!(matches!(
+            sm.common_ancestor_level(0, 13),
+            Err(MerkleError::IndexOob)
+        ));
+    }
+
+    #[test]
+    fn hardware_format_is_root_first_and_padded() {
+        let sm = ShaSMA::new((0..6u64).collect());
+        let proof = sm.prove_index(5).unwrap();
+        assert_eq!(proof.siblings.len(), 3);
+
+        let (path, index) = proof.to_hardware_format(5).unwrap();
+        assert_eq!(index, 5);
+        assert_eq!(path.len(), 5);
+        assert_eq!(path[..2], [[0u8; 32]; 2]);
+        assert_eq!(path[2], proof.siblings[2].0 .0);
+        assert_eq!(path[4], proof.siblings[0].0 .0);
+
+        // A verifier walking root to leaf, MSB-first, skipping the padding.
+        let mut acc = proof.leaf;
+        for (k, sib) in path.iter().enumerate().skip(2).rev() {
+            let sib = Hash32(*sib);
+            acc = if index >> (4 - k) & 1 == 1 {
+                Sha256Hasher::node(&sib, &acc)
+            } else {
+                Sha256Hasher::node(&acc, &sib)
+            };
+        }
+        assert_eq!(acc, sm.root());
+
+        assert!(proof.to_hardware_format(2).is_none());
+        assert!(proof.to_hardware_format(65).is_none());
+    }
+
+    #[test]
+    fn proof_index_gives_the_same_proofs() {
+        for n in [1usize, 2, 7, 16, 21] {
+            let plain = ShaSMA::new((0..n as u64).collect());
+            let indexed = plain.clone().with_proof_index();
+            assert!(indexed.sibling_offsets.as_ref().unwrap().get().is_none());
+            for i in 0..n {
+                assert_eq!(
+                    indexed.prove_index(i).unwrap(),
+                    plain.prove_index(i).unwrap()
+                );
+            }
+            assert!(matches!(indexed.prove_index(n), Err(MerkleError::IndexOob)));
+            assert_eq!(indexed.prove_all(), plain.prove_all());
+        }
+    }
+
+    #[test]
+    fn auth_path_indices_locate_proof_siblings() {
+        let sm = ShaSMA::new((0..11u64).collect());
+        for i in 0..sm.len() {
+            let path = sm.auth_path_indices(i).unwrap();
+            let proof = sm.prove_index(i).unwrap();
+            assert_eq!(path.len(), proof.siblings.len());
+            for (&(level, node), (sib, _)) in path.iter().zip(&proof.siblings) {
+                assert_eq!(sm.levels[level][node], *sib);
+            }
+        }
+        // 10 is the last of 11 leaves: its level-0 sibling is the duplicate.
+        assert_eq!(
+            sm.auth_path_indices(10).unwrap(),
+            vec![(0, 11), (1, 4), (2, 3), (3, 0)]
+        );
+        assert!(matches!(
+            sm.auth_path_indices(11),
+            Err(MerkleError::IndexOob)
+        ));
+    }
+
+    #[test]
+    fn position_bound_leaves_differ_per_index() {
+        let items = vec![5u64, 9, 5, 5];
+        let sm = ShaSMA::new_position_bound(items.clone());
+        assert!(sm.is_position_bound());
+        assert_ne!(sm.root(), ShaSMA::new(items).root());
+        assert_ne!(sm.leaf_at(&5, 0), sm.leaf_at(&5, 2));
+        assert_eq!(sm.positions_of(&5), vec![0, 2, 3]);
+
+        let proof = sm.prove_item(&5, Some(1)).unwrap();
+        assert_eq!(proof.index, 2);
+        assert!(verify_value_with_proof(&5u64, &proof));
+        assert_eq!(
+            proof.serialized_len(),
+            bincode::serialize(&proof).unwrap().len()
+        );
+
+        // Replaying the proof at another position of the same value fails.
+        let mut moved = proof.clone();
+        moved.index = 0;
+        moved.siblings = sm.prove_index(0).unwrap().siblings;
+        assert!(!verify_value_with_proof(&5u64, &moved));
+
+        let multi = sm.prove_value_multi(&5).unwrap();
+        assert!(verify_value_multi(&5u64, &multi));
+
+        let mut updated = sm.clone();
+        updated.update_leaf(1, 5).unwrap();
+        assert!(updated.verify_self_proofs());
+        assert_eq!(
+            updated.root(),
+            ShaSMA::new_position_bound(vec![5u64; 4]).root()
+        );
+    }
+
+    #[test]
+    fn structure_check_rejects_without_hashing() {
+        let sm = ShaSMA::<u64>::new((0..11).collect());
+        for p in sm.prove_all() {
+            assert!(p.is_valid_structure());
+        }
+
+        let p = sm.prove_index(6).unwrap();
+        let mut flipped = p.clone();
+        flipped.siblings[1].1 = Side::Right;
+        assert!(!flipped.is_valid_structure());
+        assert!(!flipped.verify());
+        assert!(matches!(
+            verify_value_with_proof_explain(&6u64, &flipped),
+            Err(VerifyError::Shape { .. })
+        ));
+
+        let mut short = p.clone();
+        short.siblings.pop();
+        assert!(!short.is_valid_structure());
+        let mut out_of_range = p;
+        out_of_range.index = 11;
+        assert!(!out_of_range.is_valid_structure());
+    }
+
+    #[test]
+    fn iter_proofs_for_is_lazy_and_per_index() {
+        let sm = ShaSMA::<u64>::new((0..10).collect());
+        let wanted = [9, 2, 12, 2];
+        let proofs: Vec<_> = sm.iter_proofs_for(wanted.iter().copied()).collect();
+        assert_eq!(proofs.len(), 4);
+        assert_eq!(proofs[0].as_ref().unwrap(), &sm.prove_index(9).unwrap());
+        assert_eq!(proofs[1].as_ref().unwrap(), proofs[3].as_ref().unwrap());
+        assert!(matches!(proofs[2], Err(MerkleError::IndexOob)));
+
+        // An unbounded index stream is fine as long as it's consumed lazily.
+        let evens = sm.iter_proofs_for((0..).step_by(2)).take(5);
+        assert!(evens.map(Result::unwrap).all(|p| p.verify()));
+    }
+
+    #[test]
+    fn self_proofs_catch_corruption() {
+        let arr: Vec<u64> = (0..9).collect();
+        let mut sm = StaticMerkleArray::<u64, Sha256Hasher>::new(arr);
+        assert!(sm.verify_self_proofs());
+        sm.update_leaf(8, 80).unwrap();
+        assert!(sm.verify_self_proofs());
+
+        let mut bad_leaf = sm.clone();
+        bad_leaf.levels[0][3] = Sha256Hasher::leaf(&99u64);
+        assert!(!bad_leaf.verify_self_proofs());
+
+        let mut bad_node = sm.clone();
+        bad_node.levels[2][1] = bad_node.levels[2][0];
+        assert!(!bad_node.verify_self_proofs());
+    }
+
+    #[test]
+    fn pad_to_depth_gives_uniform_proofs() {
+        let arr: Vec<u64> = (0..5).collect();
+        let sm = StaticMerkleArray::<u64, Sha256Hasher>::new(arr.clone());
+        let padded = sm.pad_to_depth(4).unwrap();
+        assert_eq!(padded.len(), 5);
+        assert_eq!(padded.leaf_count(), 16);
+        for i in 0..padded.leaf_count() {
+            let proof = padded.prove_index(i).unwrap();
+            assert_eq!(proof.siblings.len(), 4);
+            assert!(proof.verify());
+            match arr.get(i) {
+                Some(x) => assert!(verify_value_with_proof(x, &proof)),
+                None => assert_eq!(proof.leaf, Sha256Hasher::empty_leaf()),
+            }
+        }
+
+        assert_eq!(sm.pad_to_depth(3).unwrap().leaf_count(), 8);
+        assert!(matches!(
+            sm.pad_to_depth(2),
+            Err(MerkleError::TooLarge { len: 5, max: 4 })
+        ));
+    }
+
+    #[test]
+    fn padded_tree_keeps_item_leaves() {
+        let mut arr: Vec<u64> = vec![5, 1, 5, 3, 4];
+        let mut padded = ShaSMA::new(arr.clone()).pad_to_depth(3).unwrap();
+        assert!(padded.verify_self_proofs());
+        assert_eq!(padded.positions_of(&5), vec![0, 2]);
+        assert!(verify_value_with_proof(
+            &3u64,
+            &padded.prove_item(&3, None).unwrap()
+        ));
+
+        padded.update_leaf(4, 5).unwrap();
+        arr[4] = 5;
+        assert!(padded.verify_self_proofs());
+        assert_eq!(
+            padded.root(),
+            ShaSMA::new(arr.clone()).pad_to_depth(3).unwrap().root()
+        );
+        assert_eq!(padded.positions_of(&5), vec![0, 2, 4]);
+        assert!(matches!(
+            padded.update_leaf(5, 0),
+            Err(MerkleError::IndexOob)
+        ));
+
+        let (rebuilt, changed) = padded.rebuild_with_replacements(&[(1, 9)]).unwrap();
+        assert_eq!(changed, vec![1]);
+        assert_eq!(rebuilt.leaf_count(), 8);
+        assert!(rebuilt.verify_self_proofs());
+        assert_eq!(rebuilt.prove_index(7).unwrap().siblings.len(), 3);
+
+        // A padding slot holding anything but the empty leaf is caught.
+        let mut bad = padded.clone();
+        bad.levels[0][6] = Sha256Hasher::leaf(&0u64);
+        assert!(!bad.verify_self_proofs());
+    }
+
+    #[test]
+    fn subtree_roots_prove_up_to_root() {
+        let arr: Vec<u64> = (0..11).collect();
+        let sm = ShaSMA::new(arr.clone());
+
+        // Level 2 node 1 covers leaves 4..8.
+        let block = ShaSMA::new(arr[4..8].to_vec());
+        assert_eq!(sm.subtree_root(2, 1).unwrap(), block.root());
+        assert_eq!(sm.subtree_root(0, 3).unwrap(), Sha256Hasher::leaf(&3u64));
+        assert_eq!(sm.subtree_root(4, 0).unwrap(), sm.root());
+
+        for (level, width) in [(0, 11), (1, 6), (2, 3), (3, 2), (4, 1)] {
+            for i in 0..width {
+                let p = sm.prove_subtree_root(level, i).unwrap();
+                assert_eq!(p.siblings.len(), 4 - level);
+                assert!(p.verify(), "level {level} node {i}");
+            }
+            assert!(matches!(
+                sm.subtree_root(level, width),
+                Err(MerkleError::IndexOob)
+            ));
+        }
+        assert!(matches!(sm.subtree_root(5, 0), Err(MerkleError::IndexOob)));
+
+        let mut moved = sm.prove_subtree_root(2, 1).unwrap();
+        moved.level = 1;
+        assert!(!moved.verify());
+    }
+
+    /// Straightforward duplicate-padding root, independent of `try_new`.
+    fn reference_root(items: &[u64]) -> Hash32 {
+        let mut level: Vec<Hash32> = items.iter().map(Sha256Hasher::leaf).collect();
+        while level.len() > 1 {
+            if level.len() % 2 == 1 {
+                level.push(*level.last().unwrap());
+            }
+            level = level
+                .chunks(2)
+                .map(|p| Sha256Hasher::node(&p[0], &p[1]))
+                .collect();
+        }
+        level[0]
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn padding_holds_for_every_width(
+            arr in proptest::collection::vec(proptest::prelude::any::<u64>(), 1..=1000)
+        ) {
+            let sm = ShaSMA::new(arr.clone());
+            proptest::prop_assert_eq!(sm.root(), reference_root(&arr));
+            proptest::prop_assert_eq!(sm.root(), ShaSMA::new(arr.clone()).root());
+            proptest::prop_assert_eq!(sm.levels.len() - 1, depth_for_len(arr.len()));
+            for (i, v) in arr.iter().enumerate() {
+                let p = sm.prove_index(i).unwrap();
+                proptest::prop_assert!(verify_value_with_proof(v, &p), "index {} of {}", i, arr.len());
+            }
+        }
     }
 
     #[test]
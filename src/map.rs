@@ -0,0 +1,222 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Bound::{Excluded, Unbounded};
+
+use crate::{MerkleError, MerkleHasher, MerkleProof, MultiProof, StaticMerkleArray};
+
+/* -------------------------------------------------------------------------
+Merkle Map
+------------------------------------------------------------------------- */
+
+/// Key → value commitment over the entries of a `BTreeMap`, in key order.
+///
+/// Leaf `i` is `H::leaf(&(k_i, v_i))` for the `i`-th smallest key, so the root
+/// only depends on the map's contents, not on how it was built. Because the
+/// leaves are sorted, a contiguous run of leaves also proves that no other
+/// key falls between them (see `KeyRangeProof`).
+#[derive(Debug, Clone)]
+pub struct MerkleMap<K, V, H>
+where
+    K: Serialize + DeserializeOwned + Ord + Clone,
+    V: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    tree: StaticMerkleArray<(K, V), H>,
+    /// Key -> leaf index.
+    positions: BTreeMap<K, usize>,
+}
+
+impl<K, V, H> MerkleMap<K, V, H>
+where
+    K: Serialize + DeserializeOwned + Ord + Clone,
+    V: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    /// Commit to `map`'s entries in key order. Panics if `map` is empty.
+    pub fn new(map: BTreeMap<K, V>) -> Self {
+        let positions = map.keys().cloned().zip(0..).collect();
+        Self {
+            tree: StaticMerkleArray::new(map.into_iter().collect()),
+            positions,
+        }
+    }
+
+    /// Root commitment.
+    pub fn root(&self) -> H::Digest {
+        self.tree.root()
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Is the map empty? (Never true for a constructed map.)
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// The underlying array of `(key, value)` entries.
+    pub fn tree(&self) -> &StaticMerkleArray<(K, V), H> {
+        &self.tree
+    }
+
+    /// Value committed for `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let &i = self.positions.get(key)?;
+        Some(&self.tree.items[i].1)
+    }
+
+    /// Prove that `key` is in the map; the proof's leaf is `H::leaf(&(key, value))`.
+    pub fn prove_key(&self, key: &K) -> Result<MerkleProof<H>, MerkleError> {
+        let &i = self.positions.get(key).ok_or(MerkleError::NotFound)?;
+        self.tree.prove_index(i)
+    }
+
+    /// Prove exactly which entries have keys in `low..=high`.
+    ///
+    /// Besides the entries in range, the proof covers the nearest entry on
+    /// each side (when there is one), so the verifier can tell nothing was
+    /// left out. An empty range still yields a proof, of the two neighbors
+    /// around the gap.
+    pub fn prove_key_range(
+        &self,
+        low: &K,
+        high: &K,
+    ) -> Result<KeyRangeProof<K, V, H>, MerkleError> {
+        let start = self
+            .positions
+            .range(..low)
+            .next_back()
+            .map_or(0, |(_, &i)| i);
+        let end = self
+            .positions
+            .range((Excluded(high), Unbounded))
+            .next()
+            .map_or(self.len() - 1, |(_, &i)| i)
+            .max(start);
+        let indices: Vec<usize> = (start..=end).collect();
+        Ok(KeyRangeProof {
+            entries: self.tree.items[start..=end].to_vec(),
+            proof: self.tree.prove_indices(&indices)?,
+        })
+    }
+}
+
+/// Proof of the complete set of entries whose keys fall in a range.
+#[derive(Debug, Clone)]
+pub struct KeyRangeProof<K, V, H: MerkleHasher> {
+    /// Consecutive entries, including the neighbor just outside the range on
+    /// each side unless the range reaches the end of the map.
+    pub entries: Vec<(K, V)>,
+    /// Multi-proof for `entries`; `proof.indices` is contiguous.
+    pub proof: MultiProof<H>,
+}
+
+impl<K, V, H> KeyRangeProof<K, V, H>
+where
+    K: Serialize + Ord,
+    V: Serialize,
+    H: MerkleHasher,
+{
+    /// Check the proof against the trusted map `root` and `low..=high`: the
+    /// entries are authenticated under `root`, consecutive and strictly
+    /// ordered, and bracketed by keys outside the range (or by the ends of
+    /// the map).
+    ///
+    /// `root` must come from outside the proof; the one carried in `proof` is
+    /// chosen by whoever built it.
+    pub fn verify(&self, root: &H::Digest, low: &K, high: &K) -> bool {
+        let p = &self.proof;
+        let (Some(first), Some(last)) = (self.entries.first(), self.entries.last()) else {
+            return false;
+        };
+        p.root == *root
+            && p.verify()
+            && self.entries.len() == p.indices.len()
+            && p.indices.windows(2).all(|w| w[1] == w[0] + 1)
+            && self.entries.windows(2).all(|w| w[0].0 < w[1].0)
+            && self
+                .entries
+                .iter()
+                .zip(&p.leaves)
+                .all(|(e, leaf)| H::leaf(e) == *leaf)
+            && (p.indices[0] == 0 || first.0 < *low)
+            && (p.indices[p.indices.len() - 1] == p.tree_len - 1 || last.0 > *high)
+    }
+
+    /// The proven entries with keys in `low..=high`.
+    pub fn in_range<'a>(&'a self, low: &'a K, high: &'a K) -> impl Iterator<Item = &'a (K, V)> {
+        self.entries
+            .iter()
+            .filter(move |(k, _)| low <= k && k <= high)
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+
+    type Map = MerkleMap<u32, String, Sha512_256Hasher>;
+
+    fn sample() -> BTreeMap<u32, String> {
+        [10u32, 20, 30, 40, 50]
+            .into_iter()
+            .map(|k| (k, format!("v{k}")))
+            .collect()
+    }
+
+    #[test]
+    fn root_is_independent_of_insertion_order() {
+        let mut reversed = BTreeMap::new();
+        for (k, v) in sample().into_iter().rev() {
+            reversed.insert(k, v);
+        }
+        let m = Map::new(sample());
+        assert_eq!(m.root(), Map::new(reversed).root());
+
+        let proof = m.prove_key(&30).unwrap();
+        assert!(proof.verify());
+        assert_eq!(
+            proof.leaf,
+            Sha512_256Hasher::leaf(&(30u32, "v30".to_string()))
+        );
+        assert!(matches!(m.prove_key(&31), Err(MerkleError::NotFound)));
+    }
+
+    #[test]
+    fn range_proofs_are_complete() {
+        let m = Map::new(sample());
+        let cases = [
+            (15, 40, vec![20, 30, 40]),
+            (0, 10, vec![10]),
+            (45, 99, vec![50]),
+            (31, 39, vec![]),
+        ];
+        for (low, high, want) in cases {
+            let rp = m.prove_key_range(&low, &high).unwrap();
+            assert!(rp.verify(&m.root(), &low, &high), "{low}..={high}");
+            let got: Vec<u32> = rp.in_range(&low, &high).map(|(k, _)| *k).collect();
+            assert_eq!(got, want);
+        }
+
+        // A narrower proof can't be reused for a wider range, and the entries
+        // must line up with the proven indices.
+        let narrow = m.prove_key_range(&15, &25).unwrap();
+        assert!(!narrow.verify(&m.root(), &15, &35));
+        let mut rp = m.prove_key_range(&15, &40).unwrap();
+        rp.entries.pop();
+        assert!(!rp.verify(&m.root(), &15, &40));
+
+        // A proof over some other map is consistent with its own root only.
+        let mut forged = sample();
+        forged.remove(&30);
+        let forged = Map::new(forged);
+        let rp = forged.prove_key_range(&15, &40).unwrap();
+        assert!(rp.verify(&forged.root(), &15, &40));
+        assert!(!rp.verify(&m.root(), &15, &40));
+    }
+}
@@ -0,0 +1,53 @@
+//! Optional `metrics` instrumentation (the `metrics` feature).
+//!
+//! `Meter::start(op)` notes the time; `finish(ok)` then increments
+//! `static_merkle_array_verifications_total{op, result}` (`result` is `ok`
+//! or `fail`) and records the elapsed seconds in
+//! `static_merkle_array_verify_seconds{op}`, through whichever recorder the
+//! application installed. Without the feature both are no-ops.
+//!
+//! Each proof type's `verify` (and so its `Verify` impl) records one `op`:
+//! `verify` for `MerkleProof`, `verify_multi`, `verify_subtree`,
+//! `verify_sorted`, `verify_kary`, `verify_mmr` and `verify_proof_set`, plus
+//! `authenticate`. The crate's own checks (`prove_index`'s debug assertion,
+//! `verify_self_proofs`) are not recorded.
+
+#[cfg(feature = "metrics")]
+pub(crate) struct Meter {
+    op: &'static str,
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "metrics")]
+impl Meter {
+    pub(crate) fn start(op: &'static str) -> Self {
+        Self {
+            op,
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Record the outcome and pass `ok` through.
+    pub(crate) fn finish(self, ok: bool) -> bool {
+        let result = if ok { "ok" } else { "fail" };
+        metrics::counter!("static_merkle_array_verifications_total", "op" => self.op, "result" => result)
+            .increment(1);
+        metrics::histogram!("static_merkle_array_verify_seconds", "op" => self.op)
+            .record(self.start.elapsed().as_secs_f64());
+        ok
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) struct Meter;
+
+#[cfg(not(feature = "metrics"))]
+impl Meter {
+    pub(crate) fn start(_op: &'static str) -> Self {
+        Meter
+    }
+
+    pub(crate) fn finish(self, ok: bool) -> bool {
+        ok
+    }
+}
@@ -1,15 +1,49 @@
-use ark_ff::Field;
+use ark_ff::{Field, PrimeField};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
 
 use crate::hash_constants::{MIMC_ROUNDS, MIMC_ROUND_CONSTANTS_110};
 use crate::utils::int_to_fr;
 
-pub fn mimc_hash_2(a: ark_bn254::Fr, b: ark_bn254::Fr) -> ark_bn254::Fr {
+/// The published round constants, converted once.
+pub(crate) static ROUND_CONSTANTS: Lazy<Vec<ark_bn254::Fr>> = Lazy::new(|| {
+    MIMC_ROUND_CONSTANTS_110
+        .iter()
+        .take(MIMC_ROUNDS)
+        .map(|c| int_to_fr(c))
+        .collect()
+});
+
+/// A second set of `MIMC_ROUNDS` constants for node hashing.
+///
+/// Nothing-up-my-sleeve: `c_0 = 0` (as in the published set) and
+/// `c_i = SHA-256("static_merkle_array/mimc-bn254/node" || i as u64 BE) mod r`.
+pub(crate) static NODE_ROUND_CONSTANTS: Lazy<Vec<ark_bn254::Fr>> = Lazy::new(|| {
+    (0..MIMC_ROUNDS as u64)
+        .map(|i| {
+            if i == 0 {
+                return ark_bn254::Fr::from(0u64);
+            }
+            let mut h = Sha256::new();
+            h.update(b"static_merkle_array/mimc-bn254/node");
+            h.update(i.to_be_bytes());
+            ark_bn254::Fr::from_be_bytes_mod_order(&h.finalize())
+        })
+        .collect()
+});
+
+/// Two-to-one MiMC with the given round constants (one round per constant),
+/// e.g. `ROUND_CONSTANTS`.
+pub fn mimc_hash_2(
+    a: ark_bn254::Fr,
+    b: ark_bn254::Fr,
+    constants: &[ark_bn254::Fr],
+) -> ark_bn254::Fr {
     // Initialize state by summing inputs
     let mut x = a + b;
 
     // Apply MiMC round function: x = (x + c_i)^5
-    for i in 0..MIMC_ROUNDS {
-        let c = int_to_fr(MIMC_ROUND_CONSTANTS_110[i]);
+    for c in constants {
         let exponent = 5u64;
         x = (x + c).pow([exponent]);
     }
@@ -1,18 +1,23 @@
-use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use serde::ser::{self, Impossible};
+use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
 
-use ark_bn254::Fr;
+pub use ark_bn254::Fr;
 use ark_ff::fields::PrimeField;
 use ark_ff::BigInteger;
 
 
-use crate::mimc::mimc_hash_2;
+use crate::mimc::{mimc_hash_2, NODE_ROUND_CONSTANTS, ROUND_CONSTANTS};
 // Bring your Merkle trait/types into scope
-use crate::{MerkleHasher, StaticMerkleArray};
+use crate::digest::MiMCDigest;
+use crate::{HashError, MerkleHasher, StaticMerkleArray};
+
+pub use static_merkle_array_derive::FieldLeaf;
 
 /* ------------------------------- Data type -------------------------------- */
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, FieldLeaf)]
 pub struct ProductionRule {
     pub parent: (bool, u64),
     pub left_child: (bool, u64),
@@ -23,6 +28,8 @@ pub struct ProductionRule {
 
 const LEAF_DOMAIN: u64 = 0xA5; // arbitrary, distinct from node
 const NODE_DOMAIN: u64 = 0x5A;
+/// Leaves without a field-native encoding (hashed from `bincode` chunks).
+const BYTES_LEAF_DOMAIN: u64 = 0xA6;
 
 #[inline]
 fn b2f(b: bool) -> Fr {
@@ -39,7 +46,7 @@ fn u2f(x: u64) -> Fr {
 }
 
 #[inline]
-fn fr_to_bytes32(x: Fr) -> [u8; 32] {
+pub(crate) fn fr_to_bytes32(x: Fr) -> [u8; 32] {
     // ark-ff 0.2.x: Fr::into_repr() -> BigInteger256
     let mut v = x.into_bigint().to_bytes_le();
     if v.len() < 32 {
@@ -51,88 +58,487 @@ fn fr_to_bytes32(x: Fr) -> [u8; 32] {
 }
 
 #[inline]
-fn bytes32_to_fr(b: &[u8; 32]) -> Fr {
+pub(crate) fn bytes32_to_fr(b: &[u8; 32]) -> Fr {
     Fr::from_le_bytes_mod_order(b)
 }
 
 /// Hash a sequence of field elements with a MiMC-based MD-style compressor.
 /// Domain is a field element (e.g., LEAF_DOMAIN or NODE_DOMAIN).
 #[inline]
-fn hash_frs(domain: Fr, parts: &[Fr]) -> Fr {
+fn hash_frs(domain: Fr, parts: &[Fr], constants: &[Fr]) -> Fr {
     let mut h = domain;
     for m in parts {
-        h = mimc_hash_2(h, *m);
+        h = mimc_hash_2(h, *m, constants);
     }
     h
 }
 
-/// Encode a `ProductionRule` directly into field elements (no bytes).
-#[inline]
-fn rule_to_frs(r: &ProductionRule) -> [Fr; 6] {
-    [
-        b2f(r.parent.0),
-        u2f(r.parent.1),
-        b2f(r.left_child.0),
-        u2f(r.left_child.1),
-        b2f(r.right_child.0),
-        u2f(r.right_child.1),
-    ]
+/* --------------------------- Field-native leaves --------------------------- */
+
+/// Encoding of a value as a sequence of BN254 field elements.
+///
+/// `bool` maps to `0`/`1`, unsigned integers up to `u64` map to their value,
+/// and tuples, arrays and `#[derive(FieldLeaf)]` structs concatenate their
+/// fields in order.
+pub trait FieldLeaf {
+    /// Append the field elements of `self` to `out`.
+    fn to_frs(&self, out: &mut Vec<Fr>);
+}
+
+impl FieldLeaf for bool {
+    fn to_frs(&self, out: &mut Vec<Fr>) {
+        out.push(b2f(*self));
+    }
+}
+
+macro_rules! uint_field_leaf {
+    ($($t:ty),*) => {$(
+        impl FieldLeaf for $t {
+            fn to_frs(&self, out: &mut Vec<Fr>) {
+                out.push(u2f(u64::from(*self)));
+            }
+        }
+    )*};
+}
+
+uint_field_leaf!(u8, u16, u32, u64);
+
+impl<T: FieldLeaf, const N: usize> FieldLeaf for [T; N] {
+    fn to_frs(&self, out: &mut Vec<Fr>) {
+        for x in self {
+            x.to_frs(out);
+        }
+    }
+}
+
+impl<A: FieldLeaf, B: FieldLeaf> FieldLeaf for (A, B) {
+    fn to_frs(&self, out: &mut Vec<Fr>) {
+        self.0.to_frs(out);
+        self.1.to_frs(out);
+    }
+}
+
+impl<A: FieldLeaf, B: FieldLeaf, C: FieldLeaf> FieldLeaf for (A, B, C) {
+    fn to_frs(&self, out: &mut Vec<Fr>) {
+        self.0.to_frs(out);
+        self.1.to_frs(out);
+        self.2.to_frs(out);
+    }
+}
+
+/* ----------------------- Field-native serialization ------------------------ */
+
+/// Serde serializer that produces the `FieldLeaf` encoding: `bool` and
+/// unsigned integers up to `u64` are one element each; tuples, arrays, tuple
+/// structs and structs concatenate their fields in order. Anything else
+/// (signed or wider integers, floats, strings, sequences, maps, options,
+/// enums) is rejected with `NotFieldNative`.
+struct FrSerializer<'a> {
+    out: &'a mut Vec<Fr>,
+}
+
+#[derive(Debug)]
+struct NotFieldNative;
+
+impl fmt::Display for NotFieldNative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("value has no field-native encoding")
+    }
+}
+
+impl std::error::Error for NotFieldNative {}
+
+impl ser::Error for NotFieldNative {
+    fn custom<M: fmt::Display>(_msg: M) -> Self {
+        NotFieldNative
+    }
+}
+
+/// The `FieldLeaf` encoding of `item`, if its serde form has one.
+fn field_elements<T: Serialize + ?Sized>(item: &T) -> Option<Vec<Fr>> {
+    let mut out = Vec::new();
+    item.serialize(FrSerializer { out: &mut out }).ok()?;
+    Some(out)
+}
+
+impl<'a> FrSerializer<'a> {
+    fn push(self, x: u64) -> Result<(), NotFieldNative> {
+        self.out.push(u2f(x));
+        Ok(())
+    }
+}
+
+impl<'a> Serializer for FrSerializer<'a> {
+    type Ok = ();
+    type Error = NotFieldNative;
+    type SerializeSeq = Impossible<(), NotFieldNative>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Impossible<(), NotFieldNative>;
+    type SerializeMap = Impossible<(), NotFieldNative>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), NotFieldNative>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), NotFieldNative> {
+        self.out.push(b2f(v));
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), NotFieldNative> {
+        self.push(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), NotFieldNative> {
+        self.push(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), NotFieldNative> {
+        self.push(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), NotFieldNative> {
+        self.push(v)
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_str(self, _: &str) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_none(self) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_unit(self) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    /// A `#[derive(FieldLeaf)]` unit struct has no elements.
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), NotFieldNative> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), NotFieldNative> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, NotFieldNative> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, NotFieldNative> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, NotFieldNative> {
+        Err(NotFieldNative)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, NotFieldNative> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, NotFieldNative> {
+        Err(NotFieldNative)
+    }
+}
+
+impl<'a> ser::SerializeTuple for FrSerializer<'a> {
+    type Ok = ();
+    type Error = NotFieldNative;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), NotFieldNative> {
+        value.serialize(FrSerializer { out: self.out })
+    }
+
+    fn end(self) -> Result<(), NotFieldNative> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for FrSerializer<'a> {
+    type Ok = ();
+    type Error = NotFieldNative;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NotFieldNative> {
+        value.serialize(FrSerializer { out: self.out })
+    }
+
+    fn end(self) -> Result<(), NotFieldNative> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for FrSerializer<'a> {
+    type Ok = ();
+    type Error = NotFieldNative;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), NotFieldNative> {
+        value.serialize(FrSerializer { out: self.out })
+    }
+
+    fn end(self) -> Result<(), NotFieldNative> {
+        Ok(())
+    }
+}
+
+/* ----------------------------- Round constants ----------------------------- */
+
+/// Round constants used by `MiMCBn254RuleHasher` for leaves and for nodes.
+pub trait MiMCConstants {
+    /// Constants of the permutation that absorbs leaf field elements.
+    fn leaf_constants() -> &'static [Fr];
+    /// Constants of the permutation that absorbs child digests.
+    fn node_constants() -> &'static [Fr];
+}
+
+/// The published 110-round constants for both leaves and nodes (the default).
+///
+/// Leaves and nodes then differ only by their domain tag (`0xA5` vs `0x5A`),
+/// which is the first value absorbed by the chain.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SharedConstants;
+
+impl MiMCConstants for SharedConstants {
+    fn leaf_constants() -> &'static [Fr] {
+        &ROUND_CONSTANTS
+    }
+
+    fn node_constants() -> &'static [Fr] {
+        &ROUND_CONSTANTS
+    }
+}
+
+/// Published constants for leaves, a second derived set for nodes.
+///
+/// This only changes the round constants of the node chain; it does not make
+/// leaves and nodes independent. Each step of the chain is `P(acc + x)`,
+/// where `P` is the rounds `(x + c_i)^5`: a public permutation of `Fr` (`x^5`
+/// is a bijection on BN254) with no key and no feed-forward, so anyone can
+/// invert it. Whatever the constants, an input chunk can be solved for that
+/// makes a leaf hit any chosen digest, a node's included, and the same
+/// inversion gives preimages for any target. Don't rely on this hasher, with
+/// either constant set, where leaf/node separation or preimage resistance
+/// matters.
+///
+/// The node set is nothing-up-my-sleeve (`SHA-256` of a fixed label and the
+/// round number, reduced mod `r`) and the domain tags are kept. Roots differ
+/// from the default `SharedConstants` hasher.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SplitConstants;
+
+impl MiMCConstants for SplitConstants {
+    fn leaf_constants() -> &'static [Fr] {
+        &ROUND_CONSTANTS
+    }
+
+    fn node_constants() -> &'static [Fr] {
+        &NODE_ROUND_CONSTANTS
+    }
 }
 
 /* ----------------------------- The Hasher --------------------------------- */
 
+/// Field-native MiMC/BN254 hasher.
+///
+/// **Digests are little-endian.** Each `MiMCDigest` is the canonical
+/// integer of an `Fr` element in *little-endian* byte order. The EVM reads a
+/// `uint256` big-endian, so use `root_be_bytes` / `root_be_hex` (or
+/// `digest_to_be_bytes`) when submitting a root on-chain, and do not reverse
+/// their output again.
+///
+/// `C` picks the round constants; the default `SharedConstants` keeps the
+/// original roots, `SplitConstants` (see `SplitMiMCBn254RuleHasher`) uses
+/// other round constants for nodes, which is no security boundary (see
+/// `SplitConstants`).
 #[derive(Clone, Copy, Debug, Default)]
-pub struct MiMCBn254RuleHasher;
+pub struct MiMCBn254RuleHasher<C = SharedConstants>(PhantomData<C>);
+
+/// `MiMCBn254RuleHasher` with separate leaf and node round constants.
+pub type SplitMiMCBn254RuleHasher = MiMCBn254RuleHasher<SplitConstants>;
 
-impl MerkleHasher for MiMCBn254RuleHasher {
-    type Digest = [u8; 32];
+impl<C: MiMCConstants> MerkleHasher for MiMCBn254RuleHasher<C> {
+    type Digest = MiMCDigest;
 
-    /// Leaf: interpret `T` as `ProductionRule` and hash its fields as `Fr`s.
+    /// Leaf: the `FieldLeaf` encoding of `T` (taken from its serde form),
+    /// absorbed as `Fr`s after the leaf domain tag. This is what `leaf_frs`
+    /// computes for `ProductionRule` and other derived structs of `bool`s,
+    /// unsigned integers, tuples and arrays.
     ///
-    /// Note: This hasher is intended for `T = ProductionRule`. If used with a
-    /// different `T`, it falls back to a generic (field-chunked) path.
+    /// Types without a field-native encoding (strings, vectors, enums, signed
+    /// integers, ...) are hashed from their `bincode` bytes, split into 32-byte
+    /// little-endian chunks, after a separate domain tag, so they never share a
+    /// leaf with a field-native value.
+    ///
+    /// If `T` fails to serialize, the leaf is the hash of no chunks
+    /// (deterministic, but shared by every such item). `try_leaf` reports the
+    /// failure instead, so `StaticMerkleArray::try_new` returns an error.
     fn leaf<T: Serialize>(item: &T) -> Self::Digest {
-        // Fast path for ProductionRule (no allocation, no (de)serialization):
-        // SAFETY: The function is monomorphized per `T`. In typical usage
-        // we instantiate `StaticMerkleArray<ProductionRule, _>`, so `T` == ProductionRule.
-        // We avoid `unsafe` by trying a cheap bincode roundtrip to detect the type.
-        if let Ok(buf) = bincode::serialize(item) {
-            if let Ok(rule) = bincode::deserialize::<ProductionRule>(&buf) {
-                let parts = rule_to_frs(&rule);
-                let fr = hash_frs(Fr::from(LEAF_DOMAIN), &parts);
-                fr_to_bytes32(fr)
-            } else {
-                // Generic fallback: interpret the serialized bytes as a sequence of Fr elements
-                // (chunked LE, padded). Still hashes over field elements (not bytes).
-                let mut parts = Vec::<Fr>::with_capacity((buf.len() + 31) / 32);
-                for chunk in buf.chunks(32) {
-                    let mut tmp = [0u8; 32];
-                    tmp[..chunk.len()].copy_from_slice(chunk);
-                    parts.push(Fr::from_le_bytes_mod_order(&tmp));
-                }
-                let fr = hash_frs(Fr::from(LEAF_DOMAIN), &parts);
-                fr_to_bytes32(fr)
+        Self::try_leaf(item).unwrap_or_else(|_| MiMCDigest(bytes_leaf(&[], C::leaf_constants())))
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        Ok(MiMCDigest(match field_elements(item) {
+            Some(parts) => {
+                fr_to_bytes32(hash_frs(Fr::from(LEAF_DOMAIN), &parts, C::leaf_constants()))
             }
-        } else {
-            // Extremely unlikely; keep deterministic behavior.
-            let fr = hash_frs(Fr::from(LEAF_DOMAIN), &[]);
-            fr_to_bytes32(fr)
-        }
+            None => bytes_leaf(&bincode::serialize(item)?, C::leaf_constants()),
+        }))
     }
 
     /// Node: convert child digests back to `Fr` and absorb with a NODE domain.
     fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
         let l = bytes32_to_fr(left);
         let r = bytes32_to_fr(right);
-        let fr = hash_frs(Fr::from(NODE_DOMAIN), &[l, r]);
-        fr_to_bytes32(fr)
+        let fr = hash_frs(Fr::from(NODE_DOMAIN), &[l, r], C::node_constants());
+        MiMCDigest(fr_to_bytes32(fr))
+    }
+}
+
+impl<C: MiMCConstants> MiMCBn254RuleHasher<C> {
+    /// Leaf digest of `item`'s `FieldLeaf` encoding. Equals `leaf(item)` when
+    /// the serde form of `T` lays out the same fields, as derived structs do.
+    pub fn leaf_frs<T: FieldLeaf>(item: &T) -> MiMCDigest {
+        let mut parts = Vec::new();
+        item.to_frs(&mut parts);
+        MiMCDigest(fr_to_bytes32(hash_frs(
+            Fr::from(LEAF_DOMAIN),
+            &parts,
+            C::leaf_constants(),
+        )))
     }
 }
 
+/// Leaf digest from the `bincode` encoding of an item without a field-native
+/// encoding: the bytes as 32-byte little-endian chunks (the last one
+/// zero-padded), reduced into `Fr`.
+fn bytes_leaf(buf: &[u8], constants: &[Fr]) -> [u8; 32] {
+    let mut parts = Vec::<Fr>::with_capacity(buf.len().div_ceil(32));
+    for chunk in buf.chunks(32) {
+        let mut tmp = [0u8; 32];
+        tmp[..chunk.len()].copy_from_slice(chunk);
+        parts.push(Fr::from_le_bytes_mod_order(&tmp));
+    }
+    fr_to_bytes32(hash_frs(Fr::from(BYTES_LEAF_DOMAIN), &parts, constants))
+}
+
 /* ------------------------------- Type alias -------------------------------- */
 
 pub type RuleMerkle = StaticMerkleArray<ProductionRule, MiMCBn254RuleHasher>;
 
+/* ---------------------------- Big-endian export ---------------------------- */
+
+/// Convert a (little-endian) MiMC digest to big-endian `uint256` bytes.
+#[inline]
+pub fn digest_to_be_bytes(digest: &MiMCDigest) -> [u8; 32] {
+    let mut out = digest.0;
+    out.reverse();
+    out
+}
+
+/// The tree root as big-endian bytes, as the EVM interprets a `uint256`.
+pub fn root_be_bytes<T, C>(tree: &StaticMerkleArray<T, MiMCBn254RuleHasher<C>>) -> [u8; 32]
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    C: MiMCConstants,
+{
+    digest_to_be_bytes(&tree.root())
+}
+
+/// The tree root as a `0x`-prefixed big-endian hex string (64 digits).
+pub fn root_be_hex<T, C>(tree: &StaticMerkleArray<T, MiMCBn254RuleHasher<C>>) -> String
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    C: MiMCConstants,
+{
+    format!("0x{}", hex::encode(root_be_bytes(tree)))
+}
+
 /* ---------------------------------- Tests ---------------------------------- */
 
 #[cfg(test)]
@@ -178,6 +584,101 @@ mod tests {
         assert!(verify_value_with_proof(&rules[3], &p3));
     }
 
+    #[test]
+    fn derived_field_leaf_matches_rule_layout() {
+        let r = ProductionRule {
+            parent: (true, 1),
+            left_child: (false, 2),
+            right_child: (true, 3),
+        };
+        let mut frs = Vec::new();
+        r.to_frs(&mut frs);
+        assert_eq!(
+            frs,
+            vec![b2f(true), u2f(1), b2f(false), u2f(2), b2f(true), u2f(3)]
+        );
+        assert_eq!(field_elements(&r), Some(frs));
+    }
+
+    #[test]
+    fn field_leaf_fields_named_like_the_parameter() {
+        #[derive(Serialize, FieldLeaf)]
+        struct Transfer {
+            input: u64,
+            out: u64,
+        }
+        let mut frs = Vec::new();
+        Transfer { input: 1, out: 2 }.to_frs(&mut frs);
+        assert_eq!(frs, vec![u2f(1), u2f(2)]);
+    }
+
+    #[test]
+    fn leaves_follow_field_leaf_not_bincode() {
+        let r = ProductionRule {
+            parent: (true, 1),
+            left_child: (false, 2),
+            right_child: (true, 3),
+        };
+        assert_eq!(
+            MiMCBn254RuleHasher::<SharedConstants>::leaf(&r),
+            MiMCBn254RuleHasher::<SharedConstants>::leaf_frs(&r)
+        );
+
+        // Same bytes as the rule's `bincode`, but a different value: 27 `u8`s.
+        let bytes: [u8; 27] = bincode::serialize(&r).unwrap().try_into().unwrap();
+        assert_ne!(
+            MiMCBn254RuleHasher::<SharedConstants>::leaf(&bytes),
+            MiMCBn254RuleHasher::<SharedConstants>::leaf(&r)
+        );
+        // Vectors have no field-native encoding and take the bytes path.
+        assert!(field_elements(&bytes.to_vec()).is_none());
+        assert_ne!(
+            MiMCBn254RuleHasher::<SharedConstants>::leaf(&bytes.to_vec()),
+            MiMCBn254RuleHasher::<SharedConstants>::leaf(&r)
+        );
+    }
+
+    #[test]
+    fn root_be_export_matches_field_element() {
+        let rules = vec![ProductionRule {
+            parent: (true, 1),
+            left_child: (false, 2),
+            right_child: (true, 3),
+        }];
+        let sm: RuleMerkle = StaticMerkleArray::new(rules);
+        let be = root_be_bytes(&sm);
+        assert_eq!(Fr::from_be_bytes_mod_order(&be), bytes32_to_fr(&sm.root()));
+        assert_eq!(root_be_hex(&sm), format!("0x{}", hex::encode(be)));
+        assert_eq!(root_be_hex(&sm).len(), 66);
+    }
+
+    #[test]
+    fn split_constants_change_nodes_only() {
+        let rules: Vec<ProductionRule> = (0..5)
+            .map(|i| ProductionRule {
+                parent: (i % 2 == 0, i),
+                left_child: (true, i + 1),
+                right_child: (false, i + 2),
+            })
+            .collect();
+        let shared: RuleMerkle = StaticMerkleArray::new(rules.clone());
+        let split: StaticMerkleArray<ProductionRule, SplitMiMCBn254RuleHasher> =
+            StaticMerkleArray::new(rules.clone());
+
+        assert_eq!(NODE_ROUND_CONSTANTS.len(), ROUND_CONSTANTS.len());
+        assert_ne!(NODE_ROUND_CONSTANTS[1], ROUND_CONSTANTS[1]);
+        assert_eq!(
+            SplitMiMCBn254RuleHasher::leaf(&rules[0]),
+            MiMCBn254RuleHasher::<SharedConstants>::leaf(&rules[0])
+        );
+        assert_ne!(split.root(), shared.root());
+
+        for (i, rule) in rules.iter().enumerate() {
+            let proof = split.prove_index(i).unwrap();
+            assert!(verify_value_with_proof(rule, &proof));
+        }
+    }
+
     #[test]
     fn persistence_roundtrip_mimc_rule() {
         use crate::verify_value_with_proof;
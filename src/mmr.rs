@@ -0,0 +1,316 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{MerkleError, MerkleHasher, Side, Verify};
+
+/* -------------------------------------------------------------------------
+Merkle Mountain Range
+------------------------------------------------------------------------- */
+
+/// Append-only Merkle Mountain Range accumulator.
+///
+/// Leaves are grouped into perfect binary subtrees ("peaks"), one per set bit
+/// of the leaf count, largest first. Appending only touches the rightmost
+/// peaks, so there is never a full rebuild. The root bags the peaks from
+/// right to left: `node(p0, node(p1, ... node(p_{k-2}, p_{k-1})))`.
+///
+/// When the leaf count is a power of two there is a single peak and the root
+/// equals the root of `StaticMerkleArray` over the same leaves (for hashers
+/// with the default identity `finalize_root`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct MmrAccumulator<H: MerkleHasher> {
+    /// `nodes[h]` holds the roots of every complete height-`h` subtree, left to right.
+    nodes: Vec<Vec<H::Digest>>,
+    /// Number of appended leaves.
+    leaf_count: usize,
+}
+
+/// Proof that a leaf belongs to an MMR with a given root.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct MmrProof<H: MerkleHasher> {
+    /// Leaf position (0-based). Encoded as a `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub index: usize,
+    /// Number of leaves in the MMR the proof was made against. Encoded as a
+    /// `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub leaf_count: usize,
+    /// Path inside the leaf's peak, followed by the bagging path (bottom to top).
+    pub siblings: Vec<(H::Digest, Side)>,
+    /// The bagged MMR root we expect.
+    pub root: H::Digest,
+    /// The leaf hash for the proven item.
+    pub leaf: H::Digest,
+}
+
+/// Sides of the siblings a proof for `index` must have in an MMR of
+/// `leaf_count` leaves, bottom to top (`None` if `index` is out of range).
+///
+/// The peak holding `index`, and so the path inside it and the bagging
+/// steps, follows from the two numbers alone: inside the peak the sides are
+/// the bits of the leaf's offset (set means the sibling is on the left), then
+/// one `Right` for the bagged peaks to its right, if any, then one `Left` per
+/// peak to its left.
+fn expected_sides(index: usize, leaf_count: usize) -> Option<Vec<Side>> {
+    if index >= leaf_count {
+        return None;
+    }
+    let heights: Vec<usize> = (0..usize::BITS as usize)
+        .rev()
+        .filter(|h| leaf_count >> h & 1 == 1)
+        .collect();
+    let mut start = 0;
+    let mut p = 0;
+    while index >= start + (1 << heights[p]) {
+        start += 1 << heights[p];
+        p += 1;
+    }
+    let offset = index - start;
+    let mut sides: Vec<Side> = (0..heights[p])
+        .map(|h| {
+            if offset >> h & 1 == 1 {
+                Side::Left
+            } else {
+                Side::Right
+            }
+        })
+        .collect();
+    if p + 1 < heights.len() {
+        sides.push(Side::Right);
+    }
+    sides.extend(std::iter::repeat_n(Side::Left, p));
+    Some(sides)
+}
+
+impl<H: MerkleHasher> MmrProof<H> {
+    /// Check the proof's shape against `(index, leaf_count)`, then fold
+    /// `leaf` through `siblings` and compare with `root`.
+    ///
+    /// The sibling count and every side must be the ones `expected_sides`
+    /// derives, so a proof can't claim a position or peak it wasn't made for.
+    pub fn verify(&self) -> bool {
+        let meter = crate::meter::Meter::start("verify_mmr");
+        meter.finish(self.verify_unmetered())
+    }
+
+    fn verify_unmetered(&self) -> bool {
+        let Some(sides) = expected_sides(self.index, self.leaf_count) else {
+            return false;
+        };
+        if sides.len() != self.siblings.len()
+            || sides
+                .iter()
+                .zip(&self.siblings)
+                .any(|(s, (_, side))| s != side)
+        {
+            return false;
+        }
+        let mut acc = self.leaf;
+        for (sib, side) in &self.siblings {
+            acc = match side {
+                Side::Left => H::node(sib, &acc),
+                Side::Right => H::node(&acc, sib),
+            };
+        }
+        acc == self.root
+    }
+}
+
+impl<H: MerkleHasher> Verify for MmrProof<H> {
+    fn verify(&self) -> bool {
+        MmrProof::verify(self)
+    }
+}
+
+impl<H: MerkleHasher> Default for MmrAccumulator<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: MerkleHasher> MmrAccumulator<H> {
+    /// An empty accumulator.
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            leaf_count: 0,
+        }
+    }
+
+    /// Number of appended leaves.
+    pub fn len(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Has nothing been appended yet?
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count == 0
+    }
+
+    /// Append a leaf digest and return the new peaks (largest first).
+    pub fn append(&mut self, leaf: H::Digest) -> Vec<H::Digest> {
+        let mut h = 0;
+        let mut cur = leaf;
+        loop {
+            if self.nodes.len() == h {
+                self.nodes.push(Vec::new());
+            }
+            self.nodes[h].push(cur);
+            let n = self.nodes[h].len();
+            if n % 2 == 1 {
+                break;
+            }
+            // A pair completed at this height: merge into the next one.
+            cur = H::node(&self.nodes[h][n - 2], &self.nodes[h][n - 1]);
+            h += 1;
+        }
+        self.leaf_count += 1;
+        self.peaks()
+    }
+
+    /// Hash `item` with `H::leaf` and append it.
+    pub fn append_item<T: Serialize>(&mut self, item: &T) -> Vec<H::Digest> {
+        self.append(H::leaf(item))
+    }
+
+    /// Current peaks, largest (leftmost) first.
+    pub fn peaks(&self) -> Vec<H::Digest> {
+        self.peak_heights()
+            .into_iter()
+            .map(|h| *self.nodes[h].last().unwrap())
+            .collect()
+    }
+
+    /// Bagged root over the current peaks, or `None` if empty.
+    pub fn root(&self) -> Option<H::Digest> {
+        let peaks = self.peaks();
+        let (last, rest) = peaks.split_last()?;
+        Some(rest.iter().rev().fold(*last, |acc, p| H::node(p, &acc)))
+    }
+
+    /// Build a membership proof for the leaf at `leaf_index`.
+    pub fn prove(&self, leaf_index: usize) -> Result<MmrProof<H>, MerkleError> {
+        if leaf_index >= self.leaf_count {
+            return Err(MerkleError::IndexOob);
+        }
+        let heights = self.peak_heights();
+        let peaks = self.peaks();
+
+        // Locate the peak covering `leaf_index`.
+        let mut start = 0;
+        let mut p = 0;
+        while leaf_index >= start + (1 << heights[p]) {
+            start += 1 << heights[p];
+            p += 1;
+        }
+
+        // Path inside the peak.
+        let mut siblings = Vec::new();
+        for h in 0..heights[p] {
+            let i = leaf_index >> h;
+            let side = if i % 2 == 1 { Side::Left } else { Side::Right };
+            siblings.push((self.nodes[h][i ^ 1], side));
+        }
+
+        // Bagging: everything right of the peak, then each peak to its left.
+        if let Some((last, rest)) = peaks[p + 1..].split_last() {
+            let right = rest.iter().rev().fold(*last, |acc, q| H::node(q, &acc));
+            siblings.push((right, Side::Right));
+        }
+        for q in peaks[..p].iter().rev() {
+            siblings.push((*q, Side::Left));
+        }
+
+        Ok(MmrProof {
+            index: leaf_index,
+            leaf_count: self.leaf_count,
+            siblings,
+            root: self.root().expect("non-empty"),
+            leaf: self.nodes[0][leaf_index],
+        })
+    }
+
+    /// Heights of the current peaks, largest first.
+    fn peak_heights(&self) -> Vec<usize> {
+        (0..usize::BITS as usize)
+            .rev()
+            .filter(|h| self.leaf_count >> h & 1 == 1)
+            .collect()
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::StaticMerkleArray;
+
+    type Mmr = MmrAccumulator<Sha512_256Hasher>;
+
+    #[test]
+    fn append_and_prove_every_leaf() {
+        let mut mmr = Mmr::new();
+        assert!(mmr.root().is_none());
+        for x in 0u64..11 {
+            let peaks = mmr.append_item(&x);
+            assert_eq!(peaks.len(), mmr.len().count_ones() as usize);
+            for i in 0..mmr.len() {
+                let proof = mmr.prove(i).unwrap();
+                assert!(proof.verify(), "leaf {i} of {}", mmr.len());
+                assert_eq!(proof.leaf, Sha512_256Hasher::leaf(&(i as u64)));
+            }
+        }
+        assert!(matches!(mmr.prove(11), Err(MerkleError::IndexOob)));
+    }
+
+    #[test]
+    fn proofs_are_bound_to_index_and_leaf_count() {
+        let mut mmr = Mmr::new();
+        for x in 0u64..11 {
+            mmr.append_item(&x);
+        }
+        // Peaks of 8, 2 and 1 leaves; leaf 9 sits in the middle one.
+        let proof = mmr.prove(9).unwrap();
+        assert!(proof.verify());
+
+        let mut moved = proof.clone();
+        moved.index = 8;
+        assert!(!moved.verify());
+
+        let mut other_count = proof.clone();
+        other_count.leaf_count = 16;
+        assert!(!other_count.verify());
+
+        let mut flipped = proof.clone();
+        flipped.siblings[0].1 = Side::Right;
+        assert!(!flipped.verify());
+
+        let mut extra = proof.clone();
+        extra.siblings.push((proof.leaf, Side::Left));
+        assert!(!extra.verify());
+
+        let mut short = proof;
+        short.siblings.pop();
+        assert!(!short.verify());
+    }
+
+    #[test]
+    fn power_of_two_root_matches_static_array() {
+        let items: Vec<u64> = (0..8).collect();
+        let mut mmr = Mmr::new();
+        for x in &items {
+            mmr.append_item(x);
+        }
+        let sm: StaticMerkleArray<u64, Sha512_256Hasher> = StaticMerkleArray::new(items);
+        assert_eq!(mmr.root(), Some(sm.root()));
+    }
+}
@@ -0,0 +1,148 @@
+use ark_bn254::{Fq, Fr, G1Affine, G1Projective};
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+
+use crate::digest::PedersenDigest;
+use crate::{HashError, MerkleHasher, StaticMerkleArray};
+
+/* -------------------------------------------------------------------------
+Generators
+------------------------------------------------------------------------- */
+
+const LEAF_DOMAIN: u8 = 0xA5; // same tags as the MiMC hasher
+const NODE_DOMAIN: u8 = 0x5A;
+
+/// Prefix for deriving generators, so nobody knows their discrete logs.
+const GENERATOR_SEED: &[u8] = b"static_merkle_array/pedersen-bn254/generator";
+
+/// Bytes per scalar when packing leaf bytes (31 bytes < 254 bits, so packing
+/// never reduces mod r).
+const BYTES_PER_FR: usize = 31;
+
+/// The `i`-th generator of `domain`, by try-and-increment: the first
+/// `x = SHA-256(seed || domain || i || ctr) mod q` on the curve, with the
+/// smaller `y`. G1 has cofactor 1, so every curve point is in the group.
+fn generator(domain: u8, i: u64) -> G1Affine {
+    (0u32..)
+        .find_map(|ctr| {
+            let mut h = Sha256::new();
+            h.update(GENERATOR_SEED);
+            h.update([domain]);
+            h.update(i.to_be_bytes());
+            h.update(ctr.to_be_bytes());
+            let x = Fq::from_be_bytes_mod_order(&h.finalize());
+            G1Affine::get_point_from_x_unchecked(x, false)
+        })
+        .expect("a valid x within 2^32 tries")
+}
+
+/// Node generators: one per 128-bit limb of the two children.
+static NODE_GENERATORS: Lazy<[G1Affine; 4]> =
+    Lazy::new(|| std::array::from_fn(|i| generator(NODE_DOMAIN, i as u64)));
+
+/// Leaf generators, derived on demand as longer leaves show up.
+static LEAF_GENERATORS: Lazy<Mutex<Vec<G1Affine>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// `sum(scalars[i] * G_i)` over the leaf generators, extending them as needed.
+fn commit_leaf(scalars: &[Fr]) -> G1Projective {
+    let mut gens = LEAF_GENERATORS.lock().unwrap_or_else(|e| e.into_inner());
+    while gens.len() < scalars.len() {
+        let i = gens.len() as u64;
+        gens.push(generator(LEAF_DOMAIN, i));
+    }
+    G1Projective::msm_unchecked(&gens[..scalars.len()], scalars)
+}
+
+fn point_to_digest(p: G1Projective) -> PedersenDigest {
+    let mut out = [0u8; 32];
+    p.into_affine()
+        .serialize_compressed(&mut out[..])
+        .expect("compressed G1 is 32 bytes");
+    PedersenDigest(out)
+}
+
+/* ----------------------------- The Hasher --------------------------------- */
+
+/// Pedersen hash over the BN254 G1 group.
+///
+/// A leaf packs `bincode(item)` into scalars (31 bytes each, little-endian),
+/// prefixed by the byte length, and hashes them as `sum(s_i * G_i)` over the
+/// leaf generators. A node splits each child digest into two 128-bit limbs
+/// and computes `sum(limb_j * N_j)` over four node generators. Digests are
+/// the compressed result point.
+///
+/// Both encodings are injective, so finding a collision means finding a
+/// linear relation between generators, i.e. a discrete log in G1. The
+/// generators come from hashing a fixed seed and have no known relations.
+///
+/// The scheme is specific to this crate. It matches no circuit library:
+/// circomlib, Semaphore and Zcash hash Pedersen over Baby Jubjub or Jubjub
+/// with their own generators, and G1 arithmetic is non-native inside a
+/// circuit over BN254's scalar field. It is also far slower than
+/// `Poseidon2Bn254Hasher`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PedersenBn254Hasher;
+
+impl MerkleHasher for PedersenBn254Hasher {
+    type Digest = PedersenDigest;
+
+    /// Panics if `item` fails to serialize; `try_leaf` returns the error.
+    fn leaf<U: Serialize>(item: &U) -> Self::Digest {
+        Self::try_leaf(item).expect("bincode serialize")
+    }
+
+    fn try_leaf<U: Serialize>(item: &U) -> Result<Self::Digest, HashError> {
+        let bytes = bincode::serialize(item)?;
+        let scalars: Vec<Fr> = std::iter::once(Fr::from(bytes.len() as u64))
+            .chain(bytes.chunks(BYTES_PER_FR).map(Fr::from_le_bytes_mod_order))
+            .collect();
+        Ok(point_to_digest(commit_leaf(&scalars)))
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        let limbs: Vec<Fr> = [left, right]
+            .iter()
+            .flat_map(|d| d.chunks(16))
+            .map(Fr::from_le_bytes_mod_order)
+            .collect();
+        point_to_digest(G1Projective::msm_unchecked(&NODE_GENERATORS[..], &limbs))
+    }
+}
+
+/* ------------------------------- Type alias -------------------------------- */
+
+pub type PedersenMerkle<T> = StaticMerkleArray<T, PedersenBn254Hasher>;
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verify_value_with_proof;
+
+    #[test]
+    fn pedersen_tree_roundtrips_through_file() {
+        let arr: Vec<String> = (0..5).map(|i| format!("note-{i}")).collect();
+        let sm: PedersenMerkle<String> = StaticMerkleArray::new(arr.clone());
+
+        let path = std::env::temp_dir().join(format!("sma_pedersen_{}.bin", std::process::id()));
+        sm.save_to_file(&path).unwrap();
+        let loaded = PedersenMerkle::<String>::load_from_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded.root(), sm.root());
+        for (i, v) in arr.iter().enumerate() {
+            assert!(verify_value_with_proof(v, &loaded.prove_index(i).unwrap()));
+        }
+        // The length prefix keeps trailing zero bytes significant.
+        assert_ne!(
+            PedersenBn254Hasher::leaf(&vec![1u8]),
+            PedersenBn254Hasher::leaf(&vec![1u8, 0])
+        );
+    }
+}
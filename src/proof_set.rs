@@ -0,0 +1,239 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    index_bit, MerkleError, MerkleHasher, MerkleProof, Salt, Side, StaticMerkleArray, Verify,
+};
+
+/* -------------------------------------------------------------------------
+Proof Set
+------------------------------------------------------------------------- */
+
+/// One proven leaf of a `ProofSet`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(serialize = "D: Serialize", deserialize = "D: DeserializeOwned"))]
+pub struct ProofSetEntry<D> {
+    /// Proven array index. Encoded as a `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub index: usize,
+    /// The leaf hash at `index`.
+    pub leaf: D,
+    /// Siblings no other entry uses: the lower part of the path, bottom to top.
+    pub siblings: Vec<D>,
+    /// Positions in `ProofSet::pool` of the rest of the path, bottom to top.
+    /// Encoded as `u64`s on every target.
+    #[serde(with = "crate::serde_u64::vec")]
+    pub shared: Vec<usize>,
+}
+
+/// Independent single-leaf proofs that store shared upper siblings once.
+///
+/// Two leaves in the same subtree have identical siblings from that subtree
+/// up to the root, so each entry keeps its own lower siblings inline and
+/// refers to the upper ones by position in `pool`. Unlike a `MultiProof`,
+/// every entry still expands to an ordinary `MerkleProof` (see `proof`), so
+/// entries can be forwarded or checked one at a time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct ProofSet<H: MerkleHasher> {
+    /// Number of leaves in the committed array (bound into `root`). Encoded
+    /// as a `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub tree_len: usize,
+    /// The commitment root we expect.
+    pub root: H::Digest,
+    /// Salt of the tree the proofs came from (see `MerkleProof::salt`).
+    pub salt: Option<Salt>,
+    /// See `MerkleProof::position_bound`.
+    pub position_bound: bool,
+    /// Siblings used by more than one entry.
+    pub pool: Vec<H::Digest>,
+    /// One entry per requested index, in the requested order.
+    pub entries: Vec<ProofSetEntry<H::Digest>>,
+}
+
+impl<H: MerkleHasher> ProofSet<H> {
+    /// Expand entry `k` into a standalone proof; `None` if there is no such
+    /// entry or it refers past the end of `pool`.
+    pub fn proof(&self, k: usize) -> Option<MerkleProof<H>> {
+        let entry = self.entries.get(k)?;
+        let shared = entry.shared.iter().map(|&p| self.pool.get(p).copied());
+        let digests = entry
+            .siblings
+            .iter()
+            .copied()
+            .map(Some)
+            .chain(shared)
+            .collect::<Option<Vec<_>>>()?;
+        let siblings = digests
+            .into_iter()
+            .enumerate()
+            .map(|(level, d)| {
+                // Bit set: we are the right child, so the sibling is on the left.
+                let side = if index_bit(entry.index, level) {
+                    Side::Left
+                } else {
+                    Side::Right
+                };
+                (d, side)
+            })
+            .collect();
+        Some(MerkleProof {
+            index: entry.index,
+            tree_len: self.tree_len,
+            siblings,
+            root: self.root,
+            leaf: entry.leaf,
+            salt: self.salt,
+            position_bound: self.position_bound,
+        })
+    }
+
+    /// Expand every entry, in order.
+    pub fn proofs(&self) -> Option<Vec<MerkleProof<H>>> {
+        (0..self.entries.len()).map(|k| self.proof(k)).collect()
+    }
+
+    /// Whether the set is non-empty and every entry's proof verifies.
+    /// Recorded as one verification, not one per entry.
+    pub fn verify(&self) -> bool {
+        let meter = crate::meter::Meter::start("verify_proof_set");
+        meter.finish(
+            !self.entries.is_empty()
+                && (0..self.entries.len())
+                    .all(|k| self.proof(k).is_some_and(|p| p.verify_unmetered())),
+        )
+    }
+}
+
+impl<H: MerkleHasher> Verify for ProofSet<H> {
+    fn verify(&self) -> bool {
+        ProofSet::verify(self)
+    }
+}
+
+impl<T, H> StaticMerkleArray<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    /// Proofs for `indices` (in the given order) with shared upper siblings
+    /// sent once; see `ProofSet`.
+    ///
+    /// Returns `MerkleError::IndexOob` if any index is out of range, or
+    /// `MerkleError::EmptyInput` if `indices` is empty.
+    pub fn prove_set(&self, indices: &[usize]) -> Result<ProofSet<H>, MerkleError> {
+        if indices.is_empty() {
+            return Err(MerkleError::EmptyInput);
+        }
+        let ctx = self.proof_context();
+        let proofs = indices
+            .iter()
+            .map(|&i| ctx.prove(i))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // A sibling is identified by its level and position within the level.
+        let key = |index: usize, level: usize| (level, (index >> level) ^ 1);
+        let mut uses: HashMap<(usize, usize), usize> = HashMap::new();
+        for p in &proofs {
+            for level in 0..p.siblings.len() {
+                *uses.entry(key(p.index, level)).or_default() += 1;
+            }
+        }
+
+        let mut pool = Vec::new();
+        let mut pooled: HashMap<(usize, usize), usize> = HashMap::new();
+        let entries = proofs
+            .into_iter()
+            .map(|p| {
+                // Once two paths share a sibling they share every one above it.
+                let split = (0..p.siblings.len())
+                    .find(|&level| uses[&key(p.index, level)] > 1)
+                    .unwrap_or(p.siblings.len());
+                let shared = (split..p.siblings.len())
+                    .map(|level| {
+                        *pooled.entry(key(p.index, level)).or_insert_with(|| {
+                            pool.push(p.siblings[level].0);
+                            pool.len() - 1
+                        })
+                    })
+                    .collect();
+                ProofSetEntry {
+                    index: p.index,
+                    leaf: p.leaf,
+                    siblings: p.siblings[..split].iter().map(|(d, _)| *d).collect(),
+                    shared,
+                }
+            })
+            .collect();
+
+        Ok(ProofSet {
+            tree_len: self.len(),
+            root: self.root(),
+            salt: self.salt,
+            position_bound: self.position_bound,
+            pool,
+            entries,
+        })
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+
+    type Sma = StaticMerkleArray<u64, Sha512_256Hasher>;
+
+    #[test]
+    fn proof_set_expands_to_index_proofs() {
+        let sm = Sma::new((0..37).collect());
+        let indices = [8, 9, 10, 11, 36, 9];
+        let set = sm.prove_set(&indices).unwrap();
+        assert!(set.verify());
+        for (k, &i) in indices.iter().enumerate() {
+            assert_eq!(set.proof(k).unwrap(), sm.prove_index(i).unwrap());
+        }
+
+        // 8 and 9 share everything above their pair; 36 shares nothing.
+        assert_eq!(set.entries[0].siblings.len(), 1);
+        assert!(set.entries[4].shared.is_empty());
+        let separate: usize = indices
+            .iter()
+            .map(|&i| {
+                bincode::serialize(&sm.prove_index(i).unwrap())
+                    .unwrap()
+                    .len()
+            })
+            .sum();
+        let bytes = bincode::serialize(&set).unwrap();
+        assert!(bytes.len() < separate);
+        assert_eq!(bytes[..8], 37u64.to_le_bytes());
+        let back: ProofSet<Sha512_256Hasher> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back.entries, set.entries);
+        assert!(back.verify());
+    }
+
+    #[test]
+    fn tampered_proof_set_fails() {
+        let sm = Sma::new((0..16).collect());
+        let set = sm.prove_set(&[2, 3, 12]).unwrap();
+
+        let mut bad_pool = set.clone();
+        bad_pool.pool[0] = Sha512_256Hasher::leaf(&99u64);
+        assert!(!bad_pool.verify());
+
+        let mut bad_ref = set.clone();
+        bad_ref.entries[2].shared.push(bad_ref.pool.len());
+        assert!(bad_ref.proof(2).is_none());
+        assert!(!bad_ref.verify());
+
+        assert!(matches!(sm.prove_set(&[]), Err(MerkleError::EmptyInput)));
+        assert!(matches!(sm.prove_set(&[16]), Err(MerkleError::IndexOob)));
+    }
+}
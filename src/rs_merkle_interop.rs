@@ -0,0 +1,230 @@
+use crate::{index_bit, MerkleError, MerkleHasher, MerkleProof, Side};
+
+/* -------------------------------------------------------------------------
+rs_merkle-style proofs
+------------------------------------------------------------------------- */
+
+/// Split a flat sibling buffer (digests concatenated bottom to top, as
+/// produced by `rs_merkle`'s `MerkleProof::to_bytes`) into digests.
+///
+/// Fails with `MerkleError::BadProofLength` unless the buffer length is a
+/// multiple of the digest size.
+pub fn siblings_from_bytes<H>(bytes: &[u8]) -> Result<Vec<H::Digest>, MerkleError>
+where
+    H: MerkleHasher,
+    H::Digest: for<'a> TryFrom<&'a [u8]>,
+{
+    let digest_len = H::DIGEST_SIZE;
+    if digest_len == 0 || !bytes.len().is_multiple_of(digest_len) {
+        return Err(MerkleError::BadProofLength {
+            len: bytes.len(),
+            digest_len,
+        });
+    }
+    bytes
+        .chunks_exact(digest_len)
+        .map(|chunk| {
+            H::Digest::try_from(chunk).map_err(|_| MerkleError::BadProofLength {
+                len: bytes.len(),
+                digest_len,
+            })
+        })
+        .collect()
+}
+
+/// Convert an `rs_merkle`-style proof into a `MerkleProof`.
+///
+/// Those proofs carry no sides, so they are taken from the bits of `index`,
+/// as `rs_merkle` does. Where a level has an odd number of nodes,
+/// `rs_merkle` promotes the last one unchanged while this crate pairs it
+/// with itself, so the two roots only agree when no level is odd, i.e. for
+/// power-of-two `tree_len`. Other lengths fail with
+/// `MerkleError::PromotedNodes`; check those proofs with
+/// `verify_rs_merkle_proof` instead.
+pub fn proof_from_rs_merkle<H>(
+    index: usize,
+    tree_len: usize,
+    leaf: H::Digest,
+    proof_bytes: &[u8],
+    root: H::Digest,
+) -> Result<MerkleProof<H>, MerkleError>
+where
+    H: MerkleHasher,
+    H::Digest: for<'a> TryFrom<&'a [u8]>,
+{
+    if !tree_len.is_power_of_two() {
+        return Err(MerkleError::PromotedNodes { tree_len });
+    }
+    let siblings = siblings_from_bytes::<H>(proof_bytes)?
+        .into_iter()
+        .enumerate()
+        .map(|(level, d)| {
+            let side = if index_bit(index, level) {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            (d, side)
+        })
+        .collect();
+    Ok(MerkleProof {
+        index,
+        tree_len,
+        siblings,
+        root,
+        leaf,
+        salt: None,
+        position_bound: false,
+    })
+}
+
+/// Verify an `rs_merkle`-style proof directly, the way `rs_merkle` builds
+/// its tree: fold `leaf` up through the siblings with `H::node`, taking
+/// sides from the bits of `index`, and skip the levels where the node is
+/// the last of an odd number and so was promoted without a sibling.
+///
+/// Returns false for a malformed buffer, an `index` outside `tree_len`, or
+/// a sibling count that doesn't fit the path. The root is compared as is,
+/// without `H::finalize_root`.
+pub fn verify_rs_merkle_proof<H>(
+    leaf: &H::Digest,
+    index: usize,
+    tree_len: usize,
+    proof_bytes: &[u8],
+    root: &H::Digest,
+) -> bool
+where
+    H: MerkleHasher,
+    H::Digest: for<'a> TryFrom<&'a [u8]>,
+{
+    let Ok(siblings) = siblings_from_bytes::<H>(proof_bytes) else {
+        return false;
+    };
+    if index >= tree_len {
+        return false;
+    }
+    let mut siblings = siblings.iter();
+    let mut acc = *leaf;
+    let (mut pos, mut width) = (index, tree_len);
+    while width > 1 {
+        if pos + 1 < width || width % 2 == 0 {
+            let Some(sib) = siblings.next() else {
+                return false;
+            };
+            acc = if pos % 2 == 1 {
+                H::node(sib, &acc)
+            } else {
+                H::node(&acc, sib)
+            };
+        }
+        pos /= 2;
+        width = width.div_ceil(2);
+    }
+    siblings.next().is_none() && acc == *root
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fn_hasher::FnHasher;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::sorted::SortedPairHasher;
+    use crate::{fn_hasher, StaticMerkleArray};
+    use sha2::{Digest, Sha256};
+
+    type Sorted = SortedPairHasher<Sha512_256Hasher>;
+
+    #[test]
+    fn flat_proofs_roundtrip() {
+        let arr: Vec<u64> = (0..16).collect();
+        let sm: StaticMerkleArray<u64, Sorted> = StaticMerkleArray::new(arr);
+        let proof = sm.prove_index(9).unwrap();
+        let flat: Vec<u8> = proof.siblings.iter().flat_map(|(d, _)| d.0).collect();
+
+        assert!(verify_rs_merkle_proof::<Sorted>(
+            &proof.leaf,
+            9,
+            sm.len(),
+            &flat,
+            &sm.root()
+        ));
+        let converted =
+            proof_from_rs_merkle::<Sorted>(9, sm.len(), proof.leaf, &flat, sm.root()).unwrap();
+        assert_eq!(converted, proof);
+        assert!(converted.verify());
+
+        assert!(!verify_rs_merkle_proof::<Sorted>(
+            &proof.leaf,
+            9,
+            sm.len(),
+            &flat[1..],
+            &sm.root()
+        ));
+        assert!(matches!(
+            proof_from_rs_merkle::<Sorted>(9, sm.len(), proof.leaf, &flat[1..], sm.root()),
+            Err(MerkleError::BadProofLength { digest_len: 32, .. })
+        ));
+        assert!(matches!(
+            proof_from_rs_merkle::<Sorted>(9, 13, proof.leaf, &flat, sm.root()),
+            Err(MerkleError::PromotedNodes { tree_len: 13 })
+        ));
+    }
+
+    fn_hasher!(
+        RsSha256,
+        leaf = |bytes| Sha256::digest(bytes).into(),
+        node = |l, r| Sha256::new()
+            .chain_update(l)
+            .chain_update(r)
+            .finalize()
+            .into(),
+    );
+    type Rs = FnHasher<RsSha256>;
+
+    fn digest(s: &str) -> [u8; 32] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn matches_rs_merkle_vectors() {
+        // Generated with rs_merkle 1.5.0, `algorithms::Sha256`, over the
+        // leaves `Sha256::hash(&[i])` for i in 0..5. Leaf 4 is promoted
+        // twice, so its proof has a single sibling.
+        let root = digest("5174b138f822e56503c04bce38e368672593b4a2694466c2e60f1216caf234be");
+        let leaf2 = digest("dbc1b4c900ffe48d575b5da5c638040125f65db0fe3e24494b76ea986457d986");
+        let proof2 = hex::decode(
+            "084fed08b978af4d7d196a7446a86b58009e636b611db16211b65a9aadff29c5\
+             30e1867424e66e8b6d159246db94e3486778136f7e386ff5f001859d6b8484ab\
+             e52d9c508c502347344d8c07ad91cbd6068afc75ff6292f062a09ca381c89e71",
+        )
+        .unwrap();
+        let leaf4 = digest("e52d9c508c502347344d8c07ad91cbd6068afc75ff6292f062a09ca381c89e71");
+        let proof4 =
+            hex::decode("9675e04b4ba9dc81b06e81731e2d21caa2c95557a85dcfa3fff70c9ff0f30b2e")
+                .unwrap();
+
+        assert!(verify_rs_merkle_proof::<Rs>(&leaf2, 2, 5, &proof2, &root));
+        assert!(verify_rs_merkle_proof::<Rs>(&leaf4, 4, 5, &proof4, &root));
+        assert!(!verify_rs_merkle_proof::<Rs>(&leaf2, 3, 5, &proof2, &root));
+        assert!(!verify_rs_merkle_proof::<Rs>(&leaf4, 4, 6, &proof4, &root));
+        assert!(matches!(
+            proof_from_rs_merkle::<Rs>(4, 5, leaf4, &proof4, root),
+            Err(MerkleError::PromotedNodes { tree_len: 5 })
+        ));
+
+        // Over the first four leaves no level is odd, so the proof converts
+        // and the root is this crate's.
+        let root4 = digest("9675e04b4ba9dc81b06e81731e2d21caa2c95557a85dcfa3fff70c9ff0f30b2e");
+        let leaf1 = digest("4bf5122f344554c53bde2ebb8cd2b7e3d1600ad631c385a5d7cce23c7785459a");
+        let proof1 = hex::decode(
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d\
+             c2768b34413548c2a4cca10af5c71d399d9e70975a8fd428c1dc27cc0282f273",
+        )
+        .unwrap();
+        let converted = proof_from_rs_merkle::<Rs>(1, 4, leaf1, &proof1, root4).unwrap();
+        assert!(converted.verify());
+        assert!(verify_rs_merkle_proof::<Rs>(&leaf1, 1, 4, &proof1, &root4));
+    }
+}
@@ -0,0 +1,46 @@
+//! `#[serde(with = "...")]` helpers encoding `usize` positions as `u64`.
+//!
+//! The wire type is then the same on every target: a proof written on a
+//! 64-bit machine decodes on a 32-bit one whenever its values fit, and one
+//! that doesn't fit fails with an error naming the value instead of a generic
+//! type mismatch. `bincode` already wrote `usize` as 8 bytes, so existing
+//! encodings are unchanged.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub(crate) fn serialize<S: Serializer>(value: &usize, serializer: S) -> Result<S::Ok, S::Error> {
+    (*value as u64).serialize(serializer)
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    to_usize(u64::deserialize(deserializer)?)
+}
+
+fn to_usize<E: serde::de::Error>(value: u64) -> Result<usize, E> {
+    usize::try_from(value).map_err(|_| {
+        E::custom(format!(
+            "position {value} does not fit this platform's usize"
+        ))
+    })
+}
+
+/// The same for a `Vec<usize>`.
+pub(crate) mod vec {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        values: &[usize],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values.iter().map(|v| *v as u64))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<usize>, D::Error> {
+        Vec::<u64>::deserialize(deserializer)?
+            .into_iter()
+            .map(to_usize)
+            .collect()
+    }
+}
@@ -0,0 +1,143 @@
+use serde::Serialize;
+use sha3::{Digest, Keccak256, Sha3_256};
+
+use crate::digest::{Keccak256Digest, Sha3_256Digest};
+use crate::{HashError, MerkleHasher};
+
+/* ---------------------- Domain separation (bytes) ------------------------ */
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+
+/* ------------------------------- SHA3-256 --------------------------------- */
+
+/// SHA3-256 hasher (FIPS 202, `0x06` padding).
+///
+/// Not the same function as Keccak-256: the two differ only in padding, so
+/// they are easy to mix up but never agree. A leaf is
+/// `H(0x00 || bincode(item))`, a node is `H(0x01 || left || right)`.
+///
+/// The empty input tells them apart:
+///
+/// ```
+/// use static_merkle_array::sha3_hasher::Sha3_256Hasher;
+///
+/// assert_eq!(Sha3_256Hasher::NAME, "sha3-256");
+/// assert_eq!(
+///     hex::encode(Sha3_256Hasher::hash_bytes(b"")),
+///     "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha3_256Hasher;
+
+impl Sha3_256Hasher {
+    /// Stable name of the hashing scheme, as in `Commitment::algorithm`.
+    pub const NAME: &'static str = "sha3-256";
+
+    /// Plain SHA3-256 of `data`, without the leaf tag.
+    pub fn hash_bytes(data: &[u8]) -> Sha3_256Digest {
+        Sha3_256Digest(Sha3_256::digest(data).into())
+    }
+}
+
+impl MerkleHasher for Sha3_256Hasher {
+    type Digest = Sha3_256Digest;
+
+    /// Panics if `item` fails to serialize; `try_leaf` returns the error instead.
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        Self::try_leaf(item).expect("bincode serialize")
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        let enc = bincode::serialize(item)?;
+        let mut h = Sha3_256::new();
+        h.update([LEAF_TAG]);
+        h.update(&enc);
+        Ok(Sha3_256Digest(h.finalize().into()))
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        let mut h = Sha3_256::new();
+        h.update([NODE_TAG]);
+        h.update(left);
+        h.update(right);
+        Sha3_256Digest(h.finalize().into())
+    }
+}
+
+/* ------------------------------- Keccak-256 -------------------------------- */
+
+/// Keccak-256 hasher (original Keccak `0x01` padding, as used by Ethereum).
+///
+/// Same tagged encoding as `Sha3_256Hasher`, so only the padding differs.
+/// Ethereum's `keccak256` is this function:
+///
+/// ```
+/// use static_merkle_array::sha3_hasher::Keccak256Hasher;
+///
+/// assert_eq!(Keccak256Hasher::NAME, "keccak256");
+/// assert_eq!(
+///     hex::encode(Keccak256Hasher::hash_bytes(b"")),
+///     "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Keccak256Hasher;
+
+impl Keccak256Hasher {
+    /// Stable name of the hashing scheme, as in `Commitment::algorithm`.
+    pub const NAME: &'static str = "keccak256";
+
+    /// Plain Keccak-256 of `data`, without the leaf tag.
+    pub fn hash_bytes(data: &[u8]) -> Keccak256Digest {
+        Keccak256Digest(Keccak256::digest(data).into())
+    }
+}
+
+impl MerkleHasher for Keccak256Hasher {
+    type Digest = Keccak256Digest;
+
+    /// Panics if `item` fails to serialize; `try_leaf` returns the error instead.
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        Self::try_leaf(item).expect("bincode serialize")
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        let enc = bincode::serialize(item)?;
+        let mut h = Keccak256::new();
+        h.update([LEAF_TAG]);
+        h.update(&enc);
+        Ok(Keccak256Digest(h.finalize().into()))
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        let mut h = Keccak256::new();
+        h.update([NODE_TAG]);
+        h.update(left);
+        h.update(right);
+        Keccak256Digest(h.finalize().into())
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{verify_value_with_proof, StaticMerkleArray};
+
+    #[test]
+    fn sha3_and_keccak_roots_differ() {
+        let arr: Vec<u64> = (0..9).collect();
+        let sha3: StaticMerkleArray<u64, Sha3_256Hasher> = StaticMerkleArray::new(arr.clone());
+        let keccak: StaticMerkleArray<u64, Keccak256Hasher> = StaticMerkleArray::new(arr.clone());
+
+        for (i, v) in arr.iter().enumerate() {
+            assert!(verify_value_with_proof(v, &sha3.prove_index(i).unwrap()));
+            assert!(verify_value_with_proof(v, &keccak.prove_index(i).unwrap()));
+        }
+        assert_ne!(*sha3.root(), *keccak.root());
+        assert_ne!(Sha3_256Hasher::NAME, Keccak256Hasher::NAME);
+    }
+}
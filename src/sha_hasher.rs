@@ -0,0 +1,135 @@
+use bincode::Options;
+use serde::Serialize;
+use sha2::{Digest, Sha512_256};
+
+use crate::digest::Sha512_256Digest;
+use crate::{HashError, MerkleHasher};
+
+/* ---------------------- Domain separation (bytes) ------------------------ */
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+
+/* ----------------------------- The Hasher --------------------------------- */
+
+/// SHA-512/256 hasher (FIPS 180-4 truncated SHA-512, 32-byte output).
+///
+/// Uses the same encoding as the reference SHA-256 hasher: a leaf is
+/// `H(0x00 || bincode(item))`, a node is `H(0x01 || left || right)`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha512_256Hasher;
+
+impl MerkleHasher for Sha512_256Hasher {
+    type Digest = Sha512_256Digest;
+
+    /// Panics if `item` fails to serialize; use `try_leaf` (as
+    /// `StaticMerkleArray::try_new` does) to get an error instead.
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        Self::try_leaf(item).expect("bincode serialize")
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        let enc = bincode::serialize(item)?;
+        let mut h = Sha512_256::new();
+        h.update([LEAF_TAG]);
+        h.update(&enc);
+        Ok(Sha512_256Digest(h.finalize().into()))
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        let mut h = Sha512_256::new();
+        h.update([NODE_TAG]);
+        h.update(left);
+        h.update(right);
+        Sha512_256Digest(h.finalize().into())
+    }
+}
+
+/* ------------------------ Big-endian leaf encoding ------------------------ */
+
+/// SHA-512/256 hasher whose leaves use fixed-width big-endian integers.
+///
+/// A leaf is `H(0x00 || enc(item))`, where `enc` is `bincode` with big-endian
+/// byte order and fixed-width integers: a `u64` is exactly its 8 big-endian
+/// bytes, a `u32` its 4, and so on; lengths of strings and vectors are `u64`
+/// big-endian. Nodes are the same as `Sha512_256Hasher`'s. For integer leaves
+/// this matches what non-Rust tools usually hash, e.g. Python's
+/// `sha512_256(b"\x00" + x.to_bytes(8, "big"))`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Be64Hasher;
+
+impl MerkleHasher for Be64Hasher {
+    type Digest = Sha512_256Digest;
+
+    /// Panics if `item` fails to serialize; `try_leaf` returns the error instead.
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        Self::try_leaf(item).expect("bincode serialize")
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        let enc = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding()
+            .serialize(item)?;
+        let mut h = Sha512_256::new();
+        h.update([LEAF_TAG]);
+        h.update(&enc);
+        Ok(Sha512_256Digest(h.finalize().into()))
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        Sha512_256Hasher::node(left, right)
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{verify_value_with_proof, StaticMerkleArray};
+
+    /// Serializes to an error, like a map with non-string keys in some formats.
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("cannot serialize"))
+        }
+    }
+
+    #[test]
+    fn serialize_failure_is_an_error() {
+        assert!(Sha512_256Hasher::try_leaf(&Unserializable).is_err());
+        assert!(Sha512_256Hasher::try_leaf(&1u8).is_ok());
+    }
+
+    #[test]
+    fn sha512_256_prove_and_verify() {
+        let arr: Vec<u64> = (0..11).collect();
+        let sm: StaticMerkleArray<u64, Sha512_256Hasher> = StaticMerkleArray::new(arr.clone());
+
+        for (i, v) in arr.iter().enumerate() {
+            let proof = sm.prove_index(i).unwrap();
+            assert!(verify_value_with_proof(v, &proof));
+        }
+
+        // Leaf encoding is H(0x00 || bincode(item)).
+        let mut h = Sha512_256::new();
+        h.update([LEAF_TAG]);
+        h.update(bincode::serialize(&3u64).unwrap());
+        let expected: [u8; 32] = h.finalize().into();
+        assert_eq!(*Sha512_256Hasher::leaf(&3u64), expected);
+    }
+
+    #[test]
+    fn be64_leaves_are_raw_big_endian() {
+        let x = 0x0102_0304_0506_0708u64;
+        let mut h = Sha512_256::new();
+        h.update([LEAF_TAG]);
+        h.update([1, 2, 3, 4, 5, 6, 7, 8]);
+        let expected: [u8; 32] = h.finalize().into();
+        assert_eq!(*Be64Hasher::leaf(&x), expected);
+        assert_ne!(Be64Hasher::leaf(&x), Sha512_256Hasher::leaf(&x));
+    }
+}
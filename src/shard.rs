@@ -0,0 +1,126 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{level_width, MerkleHasher, Side, StaticMerkleArray, SubtreeProof};
+
+/* -------------------------------------------------------------------------
+Shards
+------------------------------------------------------------------------- */
+
+/// One shard of a tree: an aligned block of leaves, its subtree root, and the
+/// proof linking that root to the full commitment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct ShardCommitment<H: MerkleHasher> {
+    /// First leaf index covered by the shard. Encoded as a `u64` on every
+    /// target.
+    #[serde(with = "crate::serde_u64")]
+    pub start: usize,
+    /// One past the last leaf index covered (the last shard may be short).
+    #[serde(with = "crate::serde_u64")]
+    pub end: usize,
+    /// Proof of the shard's subtree root (`proof.node`) against the full root.
+    pub proof: SubtreeProof<H>,
+}
+
+impl<H: MerkleHasher> ShardCommitment<H> {
+    /// Root of the shard's subtree.
+    pub fn shard_root(&self) -> H::Digest {
+        self.proof.node
+    }
+
+    /// Check that the leaf range is the subtree's and that the subtree root
+    /// belongs to the full commitment.
+    pub fn verify(&self) -> bool {
+        let block = 1usize << self.proof.level;
+        self.start == self.proof.node_index * block
+            && self.end == (self.start + block).min(self.proof.tree_len)
+            && self.proof.verify()
+    }
+
+    /// Check a leaf against the shard root alone.
+    ///
+    /// `siblings` is the path inside the shard: the first `proof.level`
+    /// siblings of the leaf's full `MerkleProof`.
+    pub fn verify_leaf(
+        &self,
+        index: usize,
+        leaf: &H::Digest,
+        siblings: &[(H::Digest, Side)],
+    ) -> bool {
+        if index < self.start || index >= self.end || siblings.len() != self.proof.level {
+            return false;
+        }
+        let local = index - self.start;
+        siblings.iter().enumerate().all(|(h, (_, side))| {
+            let expect = if (local >> h) & 1 == 1 {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            *side == expect
+        }) && crate::verify_with_siblings::<H>(leaf, siblings, &self.proof.node)
+    }
+}
+
+impl<T, H> StaticMerkleArray<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+{
+    /// Split the tree into at most `num_shards` aligned shards.
+    ///
+    /// Shards are subtrees at the lowest level with at most `num_shards`
+    /// nodes, so each covers a power-of-two block of leaves (the last one
+    /// may be short). Panics if `num_shards` is zero.
+    pub fn shard(&self, num_shards: usize) -> Vec<ShardCommitment<H>> {
+        assert!(num_shards > 0, "need at least one shard");
+        let level = (0..self.levels.len())
+            .find(|&l| level_width(self.leaf_count(), l) <= num_shards)
+            .expect("the top level has one node");
+        let block = 1usize << level;
+        (0..level_width(self.leaf_count(), level))
+            .map(|k| ShardCommitment {
+                start: k * block,
+                end: ((k + 1) * block).min(self.leaf_count()),
+                proof: self
+                    .prove_subtree_root(level, k)
+                    .expect("node index in range"),
+            })
+            .collect()
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+
+    #[test]
+    fn shards_cover_the_array_and_verify_leaves() {
+        let sm: StaticMerkleArray<u64, Sha512_256Hasher> =
+            StaticMerkleArray::new((0..21).collect());
+        let shards = sm.shard(3);
+        assert_eq!(shards.len(), 3);
+        assert_eq!(
+            shards.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>(),
+            vec![(0, 8), (8, 16), (16, 21)]
+        );
+
+        for i in 0..sm.len() {
+            let shard = &shards[i / 8];
+            assert!(shard.verify());
+            let proof = sm.prove_index(i).unwrap();
+            let local = &proof.siblings[..shard.proof.level];
+            assert!(shard.verify_leaf(i, &proof.leaf, local));
+            assert!(!shards[(i / 8 + 1) % 3].verify_leaf(i, &proof.leaf, local));
+        }
+
+        assert_eq!(sm.shard(100).len(), 21);
+        assert_eq!(sm.shard(1)[0].shard_root(), sm.root());
+    }
+}
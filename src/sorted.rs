@@ -0,0 +1,269 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::marker::PhantomData;
+
+use crate::{HashError, MerkleHasher, MerkleProof, Verify};
+
+/* -------------------------------------------------------------------------
+Sorted-pair hashing
+------------------------------------------------------------------------- */
+
+/// Wraps a hasher so that each parent hashes its children in sorted order:
+/// `node(a, b) = H::node(min(a, b), max(a, b))`.
+///
+/// This is the "sorted pair" convention used by OpenZeppelin's `MerkleProof`
+/// and many chains. Because `node` is symmetric, proofs don't need to say on
+/// which side each sibling sits (see `SortedMerkleProof`). Leaves are `H`'s
+/// own: `leaf`, `try_leaf`, `leaf_with_index`, `try_leaf_with_index` and
+/// `empty_leaf` all forward to `H`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SortedPairHasher<H>(PhantomData<H>);
+
+impl<H> MerkleHasher for SortedPairHasher<H>
+where
+    H: MerkleHasher,
+    H::Digest: Ord,
+{
+    type Digest = H::Digest;
+
+    const SORTED: bool = true;
+
+    const DIGEST_SIZE: usize = H::DIGEST_SIZE;
+
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        H::leaf(item)
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        if left <= right {
+            H::node(left, right)
+        } else {
+            H::node(right, left)
+        }
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        H::try_leaf(item)
+    }
+
+    fn leaf_with_index<T: Serialize>(item: &T, index: usize) -> Self::Digest {
+        H::leaf_with_index(item, index)
+    }
+
+    fn try_leaf_with_index<T: Serialize>(
+        item: &T,
+        index: usize,
+    ) -> Result<Self::Digest, HashError> {
+        H::try_leaf_with_index(item, index)
+    }
+
+    fn try_node(left: &Self::Digest, right: &Self::Digest) -> Result<Self::Digest, HashError> {
+        if left <= right {
+            H::try_node(left, right)
+        } else {
+            H::try_node(right, left)
+        }
+    }
+
+    fn empty_leaf() -> Self::Digest {
+        H::empty_leaf()
+    }
+
+    fn finalize_root(top: &Self::Digest, n_leaves: usize) -> Self::Digest {
+        H::finalize_root(top, n_leaves)
+    }
+}
+
+/* -------------------------------------------------------------------------
+Sorted Merkle Proof
+------------------------------------------------------------------------- */
+
+/// A Merkle proof for a sorted-pair hasher: siblings only, no `Side`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Digest: Serialize",
+    deserialize = "H::Digest: DeserializeOwned"
+))]
+pub struct SortedMerkleProof<H: MerkleHasher> {
+    /// Original array index (0-based). Encoded as a `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub index: usize,
+    /// Number of leaves in the committed array (bound into `root`). Encoded
+    /// as a `u64` on every target.
+    #[serde(with = "crate::serde_u64")]
+    pub tree_len: usize,
+    /// Sibling hashes (bottom to top).
+    pub siblings: Vec<H::Digest>,
+    /// The commitment root we expect.
+    pub root: H::Digest,
+    /// The leaf hash for the proven item.
+    pub leaf: H::Digest,
+}
+
+impl<H: MerkleHasher> SortedMerkleProof<H> {
+    /// Fold the siblings, finalize and compare with `root`. Always false unless
+    /// `H::SORTED`, since the sides are needed otherwise.
+    pub fn verify(&self) -> bool {
+        let meter = crate::meter::Meter::start("verify_sorted");
+        meter.finish(self.verify_unmetered())
+    }
+
+    fn verify_unmetered(&self) -> bool {
+        if !H::SORTED || !crate::shape_matches(self.index, self.tree_len, self.siblings.len()) {
+            return false;
+        }
+        let acc = self
+            .siblings
+            .iter()
+            .fold(self.leaf, |acc, sib| H::node(&acc, sib));
+        H::finalize_root(&acc, self.tree_len) == self.root
+    }
+}
+
+impl<H: MerkleHasher> Verify for SortedMerkleProof<H> {
+    fn verify(&self) -> bool {
+        SortedMerkleProof::verify(self)
+    }
+}
+
+impl<H: MerkleHasher> MerkleProof<H> {
+    /// Drop the sides, for hashers with `SORTED = true`; `None` otherwise.
+    pub fn to_sorted(&self) -> Option<SortedMerkleProof<H>> {
+        H::SORTED.then(|| SortedMerkleProof {
+            index: self.index,
+            tree_len: self.tree_len,
+            siblings: self.siblings.iter().map(|(d, _)| *d).collect(),
+            root: self.root,
+            leaf: self.leaf,
+        })
+    }
+}
+
+/// `(proof, root, leaf)` as passed to Solidity's `MerkleProof.verify`.
+pub type EthProof = (Vec<[u8; 32]>, [u8; 32], [u8; 32]);
+
+/// Copy a digest's bytes into a `bytes32`, if it is exactly 32 bytes long.
+fn to_bytes32<D: AsRef<[u8]>>(d: &D) -> Option<[u8; 32]> {
+    d.as_ref().try_into().ok()
+}
+
+impl<H: MerkleHasher> MerkleProof<H>
+where
+    H::Digest: AsRef<[u8]>,
+{
+    /// The `(proof, root, leaf)` arguments of Solidity's
+    /// `MerkleProof.verify(bytes32[] proof, bytes32 root, bytes32 leaf)`:
+    /// sibling digests bottom to top without sides, then the root and leaf,
+    /// each as its 32 bytes in order (big-endian, as `bytes32` reads them).
+    ///
+    /// `None` unless `H::SORTED` and digests are 32 bytes. The contract folds
+    /// with `keccak256` of the sorted pair and compares the top with the root
+    /// as is, so the proof only passes on-chain for a `SortedPairHasher` over
+    /// a hasher with that node function and the default `finalize_root`.
+    pub fn to_eth(&self) -> Option<EthProof> {
+        if !H::SORTED {
+            return None;
+        }
+        let siblings = self
+            .siblings
+            .iter()
+            .map(|(d, _)| to_bytes32(d))
+            .collect::<Option<_>>()?;
+        Some((siblings, to_bytes32(&self.root)?, to_bytes32(&self.leaf)?))
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes_hasher::BytesHasher;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::truncated::TruncatedHasher;
+    use crate::{MerkleError, StaticMerkleArray};
+
+    type Sorted = SortedPairHasher<Sha512_256Hasher>;
+
+    #[test]
+    fn sorted_proofs_verify_without_sides() {
+        let arr: Vec<u64> = (0..11).collect();
+        let sm: StaticMerkleArray<u64, Sorted> = StaticMerkleArray::new(arr);
+        for i in 0..sm.len() {
+            let proof = sm.prove_index(i).unwrap();
+            let sorted = proof.to_sorted().unwrap();
+            assert!(Verify::verify(&proof));
+            assert!(Verify::verify(&sorted));
+            assert_eq!(sorted.siblings.len(), proof.siblings.len());
+        }
+    }
+
+    #[test]
+    fn digest_size_is_the_inner_hashers() {
+        type Short = SortedPairHasher<TruncatedHasher<Sha512_256Hasher, 16>>;
+        assert_eq!(Short::DIGEST_SIZE, 16);
+        let sm: StaticMerkleArray<u64, Short> = StaticMerkleArray::new((0..11).collect());
+        let proof = sm.prove_index(4).unwrap();
+        assert_eq!(
+            proof.serialized_len(),
+            bincode::serialize(&proof).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn leaves_are_the_inner_hashers() {
+        type SortedBytes = SortedPairHasher<BytesHasher>;
+        // BytesHasher rejects non-byte items; the wrapper reports it too.
+        assert!(matches!(
+            StaticMerkleArray::<u64, SortedBytes>::try_new(vec![1, 2]),
+            Err(MerkleError::Hasher(_))
+        ));
+
+        let item = vec![1u8, 2, 3];
+        assert_eq!(SortedBytes::empty_leaf(), BytesHasher::empty_leaf());
+        assert_eq!(
+            SortedBytes::leaf_with_index(&item, 5),
+            BytesHasher::leaf_with_index(&item, 5)
+        );
+        let bound = StaticMerkleArray::<Vec<u8>, SortedBytes>::new_position_bound(vec![item; 3]);
+        assert!(bound.verify_self_proofs());
+    }
+
+    #[test]
+    fn unsorted_hasher_has_no_sorted_proofs() {
+        let arr: Vec<u64> = (0..4).collect();
+        let sm: StaticMerkleArray<u64, Sha512_256Hasher> = StaticMerkleArray::new(arr);
+        let proof = sm.prove_index(1).unwrap();
+        assert!(proof.to_sorted().is_none());
+
+        let forced = SortedMerkleProof::<Sha512_256Hasher> {
+            index: proof.index,
+            tree_len: proof.tree_len,
+            siblings: proof.siblings.iter().map(|(d, _)| *d).collect(),
+            root: proof.root,
+            leaf: proof.leaf,
+        };
+        assert!(!forced.verify());
+        assert!(proof.to_eth().is_none());
+    }
+
+    #[test]
+    fn eth_tuple_is_the_sorted_proof_as_bytes() {
+        let arr: Vec<u64> = (0..6).collect();
+        let sm: StaticMerkleArray<u64, Sorted> = StaticMerkleArray::new(arr);
+        let proof = sm.prove_index(4).unwrap();
+        let (siblings, root, leaf) = proof.to_eth().unwrap();
+        let sorted = proof.to_sorted().unwrap();
+        assert_eq!(
+            siblings,
+            sorted.siblings.iter().map(|d| d.0).collect::<Vec<_>>()
+        );
+        assert_eq!(root, sm.root().0);
+        assert_eq!(leaf, proof.leaf.0);
+
+        // What the contract does: fold sorted pairs from the leaf up.
+        let top = siblings.iter().fold(proof.leaf, |acc, sib| {
+            Sorted::node(&acc, &crate::digest::Sha512_256Digest(*sib))
+        });
+        assert_eq!(top.0, root);
+    }
+}
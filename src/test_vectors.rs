@@ -0,0 +1,168 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{MerkleError, MerkleHasher, Side, StaticMerkleArray};
+
+/* -------------------------------------------------------------------------
+Test vectors
+------------------------------------------------------------------------- */
+
+/// Inputs, root and selected proofs of a tree, with every byte string in
+/// lowercase hex: golden files for checking other implementations against
+/// this crate's exact leaf encoding, padding and domain separation.
+///
+/// The JSON form (`to_json`) is stable:
+///
+/// ```text
+/// {
+///   "tree_len": 3,
+///   "salt": null,                    // or the 32-byte salt in hex
+///   "position_bound": false,
+///   "items": ["0100..", ..],         // bincode(item), what `H::leaf` encodes
+///   "leaves": ["..", ..],            // leaf digests, one per item
+///   "root": "..",
+///   "proofs": [
+///     { "index": 2, "leaf": "..",
+///       "siblings": [{ "digest": "..", "side": "Left" }, ..] }
+///   ]
+/// }
+/// ```
+///
+/// Sibling sides say where the sibling sits (`"Left"`: hash it first), bottom
+/// to top. Salted and position-bound trees hash `(salt, item)` and the index
+/// as documented on `new_salted` / `new_position_bound`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestVectors {
+    /// Number of items.
+    pub tree_len: usize,
+    /// The tree's leaf salt, if any.
+    pub salt: Option<String>,
+    /// Whether leaves hash in their index.
+    pub position_bound: bool,
+    /// `bincode` encoding of each item.
+    pub items: Vec<String>,
+    /// Leaf digest of each item.
+    pub leaves: Vec<String>,
+    /// Root commitment.
+    pub root: String,
+    /// Proofs for the requested indices, in the requested order.
+    pub proofs: Vec<TestVectorProof>,
+}
+
+/// One proof in `TestVectors`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestVectorProof {
+    /// Proven index.
+    pub index: usize,
+    /// Leaf digest at `index`.
+    pub leaf: String,
+    /// Authentication path, bottom to top.
+    pub siblings: Vec<TestVectorSibling>,
+}
+
+/// One sibling on a `TestVectorProof` path.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestVectorSibling {
+    /// Sibling digest.
+    pub digest: String,
+    /// Which side the sibling is on.
+    pub side: Side,
+}
+
+impl TestVectors {
+    /// Pretty-printed JSON, as committed for golden files.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("test vectors serialize")
+    }
+}
+
+impl<T, H> StaticMerkleArray<T, H>
+where
+    T: Serialize + DeserializeOwned + Eq + Clone,
+    H: MerkleHasher,
+    H::Digest: AsRef<[u8]>,
+{
+    /// Export the items, leaves, root and the proofs for `indices` as
+    /// `TestVectors`.
+    ///
+    /// Returns `MerkleError::IndexOob` if any index is out of range.
+    pub fn export_test_vectors(&self, indices: &[usize]) -> Result<TestVectors, MerkleError> {
+        let ctx = self.proof_context();
+        let proofs = indices
+            .iter()
+            .map(|&i| {
+                let proof = ctx.prove(i)?;
+                Ok(TestVectorProof {
+                    index: i,
+                    leaf: hex::encode(proof.leaf),
+                    siblings: proof
+                        .siblings
+                        .iter()
+                        .map(|(d, side)| TestVectorSibling {
+                            digest: hex::encode(d),
+                            side: *side,
+                        })
+                        .collect(),
+                })
+            })
+            .collect::<Result<_, MerkleError>>()?;
+        let items = self
+            .items
+            .iter()
+            .map(|item| Ok(hex::encode(bincode::serialize(item)?)))
+            .collect::<Result<_, MerkleError>>()?;
+
+        Ok(TestVectors {
+            tree_len: self.leaf_count(),
+            salt: self.salt.map(hex::encode),
+            position_bound: self.position_bound,
+            items,
+            leaves: self.levels[0][..self.leaf_count()]
+                .iter()
+                .map(hex::encode)
+                .collect(),
+            root: hex::encode(self.root()),
+            proofs,
+        })
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+
+    type Sma = StaticMerkleArray<u64, Sha512_256Hasher>;
+
+    #[test]
+    fn vectors_reproduce_root_from_hex() {
+        let sm = Sma::new((10..15).collect());
+        let tv = sm.export_test_vectors(&[4, 0]).unwrap();
+        assert_eq!(tv.items[0], "0a00000000000000");
+        assert_eq!(tv.leaves.len(), 5);
+        assert_eq!(tv.proofs[0].index, 4);
+
+        // Recompute the root the way another implementation would.
+        let unhex = |s: &str| -> [u8; 32] { hex::decode(s).unwrap().try_into().unwrap() };
+        for p in &tv.proofs {
+            let mut acc = crate::digest::Sha512_256Digest(unhex(&p.leaf));
+            for sib in &p.siblings {
+                let d = crate::digest::Sha512_256Digest(unhex(&sib.digest));
+                acc = match sib.side {
+                    Side::Left => Sha512_256Hasher::node(&d, &acc),
+                    Side::Right => Sha512_256Hasher::node(&acc, &d),
+                };
+            }
+            assert_eq!(hex::encode(acc), tv.root);
+        }
+
+        let json = tv.to_json();
+        assert!(json.contains("\"side\": \"Left\""));
+        assert_eq!(serde_json::from_str::<TestVectors>(&json).unwrap(), tv);
+        assert!(matches!(
+            sm.export_test_vectors(&[5]),
+            Err(MerkleError::IndexOob)
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+//! Optional `tracing` instrumentation (the `tracing` feature).
+//!
+//! `trace_span!` enters a debug-level span that logs its elapsed time when it
+//! goes out of scope; `trace_event!` emits a debug event. Without the feature
+//! both expand to nothing observable.
+
+#[cfg(feature = "tracing")]
+pub(crate) struct Timed {
+    span: tracing::span::EnteredSpan,
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "tracing")]
+impl Timed {
+    pub(crate) fn new(span: tracing::Span) -> Self {
+        Self {
+            span: span.entered(),
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Timed {
+    fn drop(&mut self) {
+        let elapsed_us = self.start.elapsed().as_micros() as u64;
+        self.span.in_scope(|| tracing::debug!(elapsed_us, "done"));
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Timed;
+
+macro_rules! trace_span {
+    ($name:expr $(, $($fields:tt)*)?) => {{
+        #[cfg(feature = "tracing")]
+        let timed = $crate::trace::Timed::new(tracing::debug_span!($name $(, $($fields)*)?));
+        #[cfg(not(feature = "tracing"))]
+        let timed = $crate::trace::Timed;
+        timed
+    }};
+}
+
+macro_rules! trace_event {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($args)*);
+    };
+}
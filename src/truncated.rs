@@ -0,0 +1,117 @@
+use serde::Serialize;
+use std::marker::PhantomData;
+
+use crate::digest::TruncatedDigest;
+use crate::{HashError, MerkleHasher};
+
+/* -------------------------------------------------------------------------
+Truncated hashing
+------------------------------------------------------------------------- */
+
+/// Wraps a 32-byte hasher and keeps only the first `N` bytes of every leaf
+/// and node digest, for smaller roots and proofs.
+///
+/// Security: an `N`-byte digest gives at most `4 * N` bits of collision
+/// resistance (birthday bound), so `N = 16` is about 64-bit and already within
+/// reach of a well-funded attacker. Only use this when the commitment is
+/// short-lived or an attacker can't choose the leaves. Second-preimage
+/// resistance degrades to `8 * N` bits.
+///
+/// A node widens its children back to 32 bytes with trailing zeros, calls
+/// `H::node` and truncates the result. `finalize_root` is applied the same way.
+/// `N` must be between 1 and 32; other values fail to compile.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TruncatedHasher<H, const N: usize>(PhantomData<H>);
+
+impl<H, const N: usize> TruncatedHasher<H, N>
+where
+    H: MerkleHasher,
+    H::Digest: AsRef<[u8]> + From<[u8; 32]>,
+{
+    const LEN_OK: () = assert!(N >= 1 && N <= 32, "TruncatedHasher needs 1 <= N <= 32");
+
+    fn truncate(full: &H::Digest) -> TruncatedDigest<N> {
+        let () = Self::LEN_OK;
+        let mut out = [0u8; N];
+        out.copy_from_slice(&full.as_ref()[..N]);
+        TruncatedDigest(out)
+    }
+
+    fn widen(short: &TruncatedDigest<N>) -> H::Digest {
+        let mut full = [0u8; 32];
+        full[..N].copy_from_slice(&short.0);
+        H::Digest::from(full)
+    }
+}
+
+impl<H, const N: usize> MerkleHasher for TruncatedHasher<H, N>
+where
+    H: MerkleHasher,
+    H::Digest: AsRef<[u8]> + From<[u8; 32]>,
+{
+    type Digest = TruncatedDigest<N>;
+
+    const SORTED: bool = H::SORTED;
+
+    const DIGEST_SIZE: usize = N;
+
+    fn leaf<T: Serialize>(item: &T) -> Self::Digest {
+        Self::truncate(&H::leaf(item))
+    }
+
+    fn try_leaf<T: Serialize>(item: &T) -> Result<Self::Digest, HashError> {
+        Ok(Self::truncate(&H::try_leaf(item)?))
+    }
+
+    fn node(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        Self::truncate(&H::node(&Self::widen(left), &Self::widen(right)))
+    }
+
+    fn try_node(left: &Self::Digest, right: &Self::Digest) -> Result<Self::Digest, HashError> {
+        Ok(Self::truncate(&H::try_node(
+            &Self::widen(left),
+            &Self::widen(right),
+        )?))
+    }
+
+    fn finalize_root(top: &Self::Digest, n_leaves: usize) -> Self::Digest {
+        Self::truncate(&H::finalize_root(&Self::widen(top), n_leaves))
+    }
+
+    fn empty_leaf() -> Self::Digest {
+        Self::truncate(&H::empty_leaf())
+    }
+}
+
+/* ---------------------------------- Tests ---------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha_hasher::Sha512_256Hasher;
+    use crate::StaticMerkleArray;
+
+    type Short = TruncatedHasher<Sha512_256Hasher, 16>;
+
+    #[test]
+    fn truncated_proofs_verify_and_shrink() {
+        let arr: Vec<u64> = (0..13).collect();
+        let short: StaticMerkleArray<u64, Short> = StaticMerkleArray::new(arr.clone());
+        let full: StaticMerkleArray<u64, Sha512_256Hasher> = StaticMerkleArray::new(arr);
+        for i in 0..short.len() {
+            let proof = short.prove_index(i).unwrap();
+            assert!(proof.verify());
+            assert_eq!(proof.leaf.0[..], Sha512_256Hasher::leaf(&(i as u64))[..16]);
+        }
+        assert_eq!(
+            Short::DIGEST_SIZE,
+            bincode::serialize(&short.root()).unwrap().len()
+        );
+        let a = short.prove_index(3).unwrap();
+        let b = full.prove_index(3).unwrap();
+        assert_eq!(
+            a.serialized_len() + 16 * (2 + a.siblings.len()),
+            b.serialized_len()
+        );
+    }
+}